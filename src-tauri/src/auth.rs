@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::models::user::{User, UserInfo, Role};
+
+const MIN_PASSWORD_LEN: usize = 6;
+
+/// Capacidades verificadas pelos comandos do Tauri.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Consultar cadastros, processos e histórico.
+    View,
+    /// Relatórios de qualidade.
+    ViewReports,
+    /// Executar sprints (pesagem).
+    OperateSprints,
+    /// Criar e finalizar processos.
+    ManageProcessos,
+    /// Cadastrar fornecedores e itens.
+    EditCadastros,
+    /// Criar e alterar fórmulas.
+    EditFormulas,
+    /// Aprovar fórmulas.
+    ApproveFormulas,
+    /// Anular sprints já concluídos.
+    VoidSprints,
+    /// Liberar sprints com itens fora da tolerância.
+    OverrideTolerances,
+    /// Aprovar o fechamento do lote.
+    SignOffBatches,
+    /// Configurar as balanças da estação.
+    ManageScales,
+    /// Excluir processos e limpar sprints.
+    DeleteRecords,
+    /// Criar e gerenciar usuários.
+    ManageUsers,
+}
+
+impl Role {
+    /// Tabela de capacidades de cada papel.
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            Role::Admin => &[View, ViewReports, OperateSprints, ManageProcessos, EditCadastros, EditFormulas, ApproveFormulas, VoidSprints, OverrideTolerances, SignOffBatches, ManageScales, DeleteRecords, ManageUsers],
+            Role::Supervisor => &[View, ViewReports, OperateSprints, ManageProcessos, EditCadastros, ApproveFormulas, VoidSprints, OverrideTolerances, ManageScales],
+            Role::Quality => &[View, ViewReports, SignOffBatches],
+            Role::User => &[View, OperateSprints, ManageProcessos, EditCadastros],
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/// Sessão aberta por um login bem-sucedido.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub user_id: String,
+    pub username: String,
+    pub role: Role,
+    pub must_change_password: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoginResponse {
+    pub token: String,
+    pub user: UserInfo,
+    pub must_change_password: bool,
+    pub permissions: Vec<Permission>,
+}
+
+/// Sessões ativas, mantidas apenas em memória no estado do Tauri.
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    pub fn open(&self, user: &User) -> Session {
+        let session = Session {
+            token: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            role: user.role.clone(),
+            must_change_password: user.must_change_password,
+            created_at: Utc::now(),
+        };
+        self.sessions.lock().unwrap().insert(session.token.clone(), session.clone());
+        session
+    }
+
+    pub fn get(&self, token: &str) -> Option<Session> {
+        self.sessions.lock().unwrap().get(token).cloned()
+    }
+
+    pub fn close(&self, token: &str) {
+        self.sessions.lock().unwrap().remove(token);
+    }
+
+    /// Retorna a sessão do token ou erro se ela não existir.
+    pub fn require(&self, token: &str) -> Result<Session, String> {
+        self.get(token).ok_or("Sessão inválida ou expirada. Faça login novamente.".to_string())
+    }
+
+    fn clear_password_flag(&self, token: &str) {
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(token) {
+            session.must_change_password = false;
+        }
+    }
+}
+
+/// Resolve o usuário logado a partir do token da sessão.
+///
+/// Sessões que ainda precisam trocar a senha não podem executar operações.
+pub fn require_user(db: &sled::Db, sessions: &SessionStore, token: &str) -> Result<User, String> {
+    let session = sessions.require(token)?;
+    if session.must_change_password {
+        return Err("Troque a senha antes de continuar".to_string());
+    }
+    User::get_by_id(&session.user_id, db)
+        .map_err(|e| e.to_string())?
+        .ok_or("Usuário da sessão não encontrado".to_string())
+}
+
+/// Resolve o usuário logado e confere se o papel dele tem a permissão exigida.
+pub fn authorize(db: &sled::Db, sessions: &SessionStore, token: &str, permission: Permission) -> Result<User, String> {
+    let user = require_user(db, sessions, token)?;
+    if !user.role.can(permission) {
+        return Err(format!("Permissão negada: o perfil {:?} não pode executar esta operação", user.role));
+    }
+    Ok(user)
+}
+
+pub fn validate_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!("A senha deve ter pelo menos {} caracteres", MIN_PASSWORD_LEN));
+    }
+    Ok(())
+}
+
+/// Confere as credenciais e abre uma sessão.
+///
+/// Senhas legadas em texto puro são regravadas com hash no primeiro login e,
+/// como ficaram expostas no banco, o usuário é obrigado a trocá-las.
+pub fn login(db: &sled::Db, sessions: &SessionStore, username: &str, password: &str) -> Result<LoginResponse, String> {
+    let invalid = || "Usuário ou senha inválidos".to_string();
+    let mut user = User::find_by_username(username, db)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(invalid)?;
+
+    if !user.verify_password(password) {
+        return Err(invalid());
+    }
+
+    if user.has_legacy_password() {
+        user.set_password(password).map_err(|e| e.to_string())?;
+        user.must_change_password = true;
+        user.save(db).map_err(|e| e.to_string())?;
+    }
+
+    let session = sessions.open(&user);
+    Ok(LoginResponse {
+        token: session.token,
+        user: UserInfo::from(&user),
+        must_change_password: user.must_change_password,
+        permissions: user.role.permissions().to_vec(),
+    })
+}
+
+/// Troca a senha do usuário da sessão, exigindo a senha atual.
+pub fn change_password(db: &sled::Db, sessions: &SessionStore, token: &str, current_password: &str, new_password: &str) -> Result<(), String> {
+    let session = sessions.require(token)?;
+    validate_password(new_password)?;
+    if current_password == new_password {
+        return Err("A nova senha deve ser diferente da atual".to_string());
+    }
+
+    let mut user = User::get_by_id(&session.user_id, db)
+        .map_err(|e| e.to_string())?
+        .ok_or("Usuário não encontrado".to_string())?;
+
+    if !user.verify_password(current_password) {
+        return Err("Senha atual incorreta".to_string());
+    }
+
+    user.set_password(new_password).map_err(|e| e.to_string())?;
+    user.must_change_password = false;
+    user.save(db).map_err(|e| e.to_string())?;
+    sessions.clear_password_flag(token);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db() -> sled::Db {
+        sled::Config::default().temporary(true).open().unwrap()
+    }
+
+    #[test]
+    fn test_password_is_hashed_and_verified() {
+        let user = User::new("op".to_string(), "segredo".to_string(), Role::User).unwrap();
+        assert_ne!(user.hashed_password, "segredo");
+        assert!(user.hashed_password.starts_with("$argon2"));
+        assert!(user.verify_password("segredo"));
+        assert!(!user.verify_password("errada"));
+    }
+
+    #[test]
+    fn test_seeded_admin_must_change_password() {
+        let db = temp_db();
+        let sessions = SessionStore::default();
+        crate::models::create_adm_if_not_exists(&db).unwrap();
+
+        assert!(login(&db, &sessions, "admin", "errada").is_err());
+        let resp = login(&db, &sessions, "admin", "admin").unwrap();
+        assert!(resp.must_change_password);
+
+        change_password(&db, &sessions, &resp.token, "admin", "nova-senha").unwrap();
+        assert!(!sessions.require(&resp.token).unwrap().must_change_password);
+
+        let resp = login(&db, &sessions, "admin", "nova-senha").unwrap();
+        assert!(!resp.must_change_password);
+    }
+
+    #[test]
+    fn test_require_user_resolves_session_user() {
+        let db = temp_db();
+        let sessions = SessionStore::default();
+        let user = User::new("op".to_string(), "segredo".to_string(), Role::User).unwrap();
+        user.save(&db).unwrap();
+
+        assert!(require_user(&db, &sessions, "token-falso").is_err());
+        let resp = login(&db, &sessions, "op", "segredo").unwrap();
+        assert_eq!(require_user(&db, &sessions, &resp.token).unwrap().id, user.id);
+
+        sessions.close(&resp.token);
+        assert!(require_user(&db, &sessions, &resp.token).is_err());
+    }
+
+    #[test]
+    fn test_authorize_checks_role_permissions() {
+        let db = temp_db();
+        let sessions = SessionStore::default();
+        for (name, role) in [("op", Role::User), ("sup", Role::Supervisor), ("qa", Role::Quality)] {
+            User::new(name.to_string(), "segredo".to_string(), role).unwrap().save(&db).unwrap();
+        }
+        let op = login(&db, &sessions, "op", "segredo").unwrap().token;
+        let sup = login(&db, &sessions, "sup", "segredo").unwrap().token;
+        let qa = login(&db, &sessions, "qa", "segredo").unwrap().token;
+
+        assert!(authorize(&db, &sessions, &op, Permission::OperateSprints).is_ok());
+        assert!(authorize(&db, &sessions, &op, Permission::DeleteRecords).is_err());
+        assert!(authorize(&db, &sessions, &op, Permission::EditFormulas).is_err());
+        assert!(authorize(&db, &sessions, &sup, Permission::ApproveFormulas).is_ok());
+        assert!(authorize(&db, &sessions, &sup, Permission::EditFormulas).is_err());
+        assert!(authorize(&db, &sessions, &sup, Permission::VoidSprints).is_ok());
+        assert!(authorize(&db, &sessions, &op, Permission::VoidSprints).is_err());
+        assert!(authorize(&db, &sessions, &qa, Permission::ViewReports).is_ok());
+        assert!(authorize(&db, &sessions, &qa, Permission::SignOffBatches).is_ok());
+        assert!(authorize(&db, &sessions, &sup, Permission::SignOffBatches).is_err());
+        assert!(authorize(&db, &sessions, &sup, Permission::ManageScales).is_ok());
+        assert!(authorize(&db, &sessions, &op, Permission::ManageScales).is_err());
+        assert!(authorize(&db, &sessions, &qa, Permission::OperateSprints).is_err());
+    }
+
+    #[test]
+    fn test_legacy_plaintext_password_is_rehashed() {
+        let db = temp_db();
+        let sessions = SessionStore::default();
+        let mut user = User::new("legado".to_string(), "x".to_string(), Role::User).unwrap();
+        user.hashed_password = "texto-puro".to_string();
+        user.save(&db).unwrap();
+
+        let resp = login(&db, &sessions, "legado", "texto-puro").unwrap();
+        assert!(resp.must_change_password);
+        let stored = User::find_by_username("legado", &db).unwrap().unwrap();
+        assert!(!stored.has_legacy_password());
+        assert!(stored.verify_password("texto-puro"));
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+/// Registro que impede uma exclusão.
+#[derive(Serialize, Debug, Clone)]
+pub struct Dependent {
+    pub kind: String,
+    pub id: String,
+    pub nome: String,
+}
+
+impl Dependent {
+    pub fn new(kind: &str, id: &str, nome: &str) -> Self {
+        Dependent { kind: kind.to_string(), id: id.to_string(), nome: nome.to_string() }
+    }
+}
+
+/// Erro estruturado devolvido ao frontend pelos comandos que precisam de mais
+/// que uma mensagem (ex.: exclusão bloqueada por registros dependentes).
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    Message { message: String },
+    HasDependents { message: String, dependents: Vec<Dependent> },
+}
+
+impl CommandError {
+    /// Bloqueia a operação se houver dependentes; caso contrário segue normalmente.
+    pub fn check_dependents(message: &str, dependents: Vec<Dependent>) -> Result<(), CommandError> {
+        if dependents.is_empty() {
+            return Ok(());
+        }
+        Err(CommandError::HasDependents { message: message.to_string(), dependents })
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Message { message }
+    }
+}
@@ -1,12 +1,29 @@
 mod models;
 mod trial;
+mod auth;
+mod error;
+mod scale;
 
-use models::processo::Processo;
-use models::formula::Formula;
+use models::processo::{Processo, ProcessoProgress, ProcessoSettings, ProcessoStatus};
+use models::formula::{Formula, FormulaLineInput, ItemFormula};
 use models::fornecedor::Fornecedor;
 use models::item::Item;
+use models::sprint::{Addition, Sprint, SprintItem, Suggestion};
+use models::audit::AuditEntry;
+use models::compensation::CompensationStrategy;
+use models::reconciliation::Reconciliation;
+use models::item::ItemMeasure;
+use models::quantity::Mass;
+use models::page::{Page, Sort, SortDirection, SortField};
+use models::resolver::{FormulaView, ItemView, ProcessoView, Resolver, SprintView};
 use crate::models::auditable::Auditable;
+use auth::{Permission, SessionStore};
+use error::{CommandError, Dependent};
+use scale::{ScaleConfig, ScaleEvent, ScaleManager, ScaleStatus, SCALE_EVENT, SIMULATOR_ID};
+use scale::driver::Command;
+use scale::protocol::ProtocolKind;
 use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
@@ -20,270 +37,941 @@ fn get_trial_info() -> Result<(i64, String), String> {
     trial::get_trial_info()
 }
 
+#[tauri::command]
+fn login(username: String, password: String, sessions: State<'_, SessionStore>) -> Result<auth::LoginResponse, String> {
+    let db = models::connect_db();
+    auth::login(db, &sessions, &username, &password)
+}
+
+#[tauri::command]
+fn logout(token: String, sessions: State<'_, SessionStore>) {
+    sessions.close(&token);
+}
+
+#[tauri::command]
+fn change_password(token: String, current_password: String, new_password: String, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::change_password(db, &sessions, &token, &current_password, &new_password)
+}
+
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
 #[tauri::command]
-fn list_processos(page: usize, page_size: usize) -> Result<Vec<Processo>, String> {
+fn list_processos(token: String, page: usize, page_size: usize, cursor: Option<String>, sort_by: Option<SortField>, direction: Option<SortDirection>, sessions: State<'_, SessionStore>) -> Result<Page<ProcessoView>, String> {
     let db = models::connect_db();
-    Processo::get_all(&db, page, page_size)
-        .map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let processos = Processo::get_all(db, page, page_size, cursor.as_deref(), Sort::from_params(sort_by, direction))
+        .map_err(|e| e.to_string())?;
+    Resolver::new(db).page(processos, Resolver::processo).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_processo(id: String) -> Result<Option<Processo>, String> {
+fn get_processo(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<Option<ProcessoView>, String> {
     let db = models::connect_db();
-    let tree = db.open_tree("processos").map_err(|e| e.to_string())?;
-    match tree.get(id.as_bytes()).map_err(|e| e.to_string())? {
-        Some(bytes) => {
-            let processo: Processo = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
-            Ok(Some(processo))
-        }
-        None => Ok(None)
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    match load_processo(db, &id)? {
+        Some(processo) => Resolver::new(db).processo(&processo).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
     }
 }
 
+fn load_processo(db: &sled::Db, id: &str) -> Result<Option<Processo>, String> {
+    Processo::get_by_id(id, db).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-fn list_formulas(page: usize, page_size: usize) -> Result<Vec<Formula>, String> {
+fn list_formulas(token: String, page: usize, page_size: usize, cursor: Option<String>, sort_by: Option<SortField>, direction: Option<SortDirection>, sessions: State<'_, SessionStore>) -> Result<Page<FormulaView>, String> {
     let db = models::connect_db();
-    Formula::get_all_paginated(&db, page, page_size)
-        .map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let formulas = Formula::get_all_paginated(db, page, page_size, cursor.as_deref(), Sort::from_params(sort_by, direction))
+        .map_err(|e| e.to_string())?;
+    Resolver::new(db).page(formulas, Resolver::formula).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn create_processo(nome: String, formula_id: String) -> Result<models::processo::Processo, String> {
+fn create_processo(token: String, nome: String, formula_id: String, settings: Option<ProcessoSettings>, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
     let db = models::connect_db();
-    let tree = db.open_tree("formulas").map_err(|e| e.to_string())?;
-    let formula_bytes = tree.get(formula_id.as_bytes()).map_err(|e| e.to_string())?
+    auth::authorize(db, &sessions, &token, Permission::ManageProcessos)?;
+    let settings = settings.unwrap_or_default();
+    settings.compensation.validate()?;
+    let formula = Formula::get_by_id(&formula_id, db).map_err(|e| e.to_string())?
         .ok_or("Fórmula não encontrada".to_string())?;
-    let formula: models::formula::Formula = serde_json::from_slice(&formula_bytes).map_err(|e| e.to_string())?;
     let weight: f64 = formula.itens.iter().map(|it| it.peso).sum();
-    let processo = models::processo::Processo::new(nome, formula, "Em Andamento".to_string(), weight);
-    processo.save(&db).map_err(|e| e.to_string())?;
-    Ok(processo)
+    let mut resolver = Resolver::new(db);
+    // o processo guarda uma cópia da fórmula como estava neste momento
+    let snapshot = resolver.formula(&formula).map_err(|e| e.to_string())?;
+    let mut processo = models::processo::Processo::new(nome, snapshot, weight);
+    processo.compensation = settings.compensation;
+    processo.set_mixer_capacity(settings.mixer_capacity).map_err(|e| e.to_string())?;
+    if settings.resolution.is_some_and(|r| r <= Mass::ZERO) {
+        return Err("Resolução inválida".to_string());
+    }
+    processo.resolution = settings.resolution;
+    processo.set_plan(settings.target_mass, settings.planned_sprints).map_err(|e| e.to_string())?;
+    processo.save(db).map_err(|e| e.to_string())?;
+    resolver.processo(&processo).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn list_fornecedores(page: usize, page_size: usize) -> Result<Vec<Fornecedor>, String> {
+fn list_fornecedores(token: String, page: usize, page_size: usize, cursor: Option<String>, sort_by: Option<SortField>, direction: Option<SortDirection>, sessions: State<'_, SessionStore>) -> Result<Page<Fornecedor>, String> {
     let db = models::connect_db();
-    Fornecedor::get_all_paginated(&db, page, page_size)
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    Fornecedor::get_all_paginated(db, page, page_size, cursor.as_deref(), Sort::from_params(sort_by, direction))
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn list_itens(page: usize, page_size: usize) -> Result<Vec<Item>, String> {
+fn list_itens(token: String, page: usize, page_size: usize, cursor: Option<String>, sort_by: Option<SortField>, direction: Option<SortDirection>, sessions: State<'_, SessionStore>) -> Result<Page<ItemView>, String> {
     let db = models::connect_db();
-    Item::get_all(&db, page, page_size)
-        .map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let itens = Item::get_all(db, page, page_size, cursor.as_deref(), Sort::from_params(sort_by, direction))
+        .map_err(|e| e.to_string())?;
+    Resolver::new(db).page(itens, Resolver::item).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn search_fornecedores(token: String, name: String, page: usize, page_size: usize, sessions: State<'_, SessionStore>) -> Result<Page<Fornecedor>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    Fornecedor::list_by_name(&name, db, page, page_size).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn search_fornecedores(name: String, page: usize, page_size: usize) -> Result<Vec<Fornecedor>, String> {
+fn search_itens(token: String, name: String, page: usize, page_size: usize, sessions: State<'_, SessionStore>) -> Result<Page<ItemView>, String> {
     let db = models::connect_db();
-    Fornecedor::list_by_name(&name, &db, page, page_size).map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let itens = Item::list_by_name(&name, db, page, page_size).map_err(|e| e.to_string())?;
+    Resolver::new(db).page(itens, Resolver::item).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn search_itens(name: String, page: usize, page_size: usize) -> Result<Vec<Item>, String> {
+fn search_processos(token: String, name: String, page: usize, page_size: usize, sessions: State<'_, SessionStore>) -> Result<Page<ProcessoView>, String> {
     let db = models::connect_db();
-    Item::list_by_name(&name, &db, page, page_size).map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let processos = Processo::list_by_name(&name, db, page, page_size).map_err(|e| e.to_string())?;
+    Resolver::new(db).page(processos, Resolver::processo).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn suggest_sprint_targets(processo_id: String, remaining_sprints: usize) -> Result<HashMap<String, f64>, String> {
-    match get_processo(processo_id)? {
-        Some(processo) => Ok(processo.suggest_next_sprint_targets(remaining_sprints)),
+fn suggest_sprint_targets(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<HashMap<String, Suggestion>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    match load_processo(db, &processo_id)? {
+        Some(processo) => Ok(processo.suggest_next_sprint_targets()),
         None => Err("Processo não encontrado".to_string())
     }
 }
 
 #[tauri::command]
-fn create_fornecedor(nome: String) -> Result<Fornecedor, String> {
+fn create_fornecedor(token: String, nome: String, sessions: State<'_, SessionStore>) -> Result<Fornecedor, String> {
     let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
     let f = models::fornecedor::Fornecedor::new(nome);
-    f.save(&db).map_err(|e| e.to_string())?;
+    f.save(db).map_err(|e| e.to_string())?;
     Ok(f)
 }
 
 #[tauri::command]
-fn create_item(nome: String, fornecedor_id: String) -> Result<Item, String> {
+fn create_item(token: String, nome: String, fornecedor_id: String, measure: Option<ItemMeasure>, sessions: State<'_, SessionStore>) -> Result<ItemView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
+    if Fornecedor::get_by_id(&fornecedor_id, db).map_err(|e| e.to_string())?.is_none() {
+        return Err("Fornecedor não encontrado".to_string());
+    }
+    let mut item = models::item::Item::new(nome, fornecedor_id);
+    item.set_measure(measure.unwrap_or_default())?;
+    item.save(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).item(&item).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_formula(token: String, nome: String, itens: Vec<FormulaLineInput>, sessions: State<'_, SessionStore>) -> Result<FormulaView, String> {
     let db = models::connect_db();
-    let fornecedores = Fornecedor::get_all_paginated(&db, 0, 1000).map_err(|e| e.to_string())?;
-    let fornecedor = fornecedores.into_iter().find(|f| f.id == fornecedor_id).ok_or("Fornecedor não encontrado".to_string())?;
-    let item = models::item::Item::new(nome, fornecedor);
-    item.save(&db).map_err(|e| e.to_string())?;
-    Ok(item)
+    auth::authorize(db, &sessions, &token, Permission::EditFormulas)?;
+    let mut formula = models::formula::Formula::new(nome, Vec::new());
+    formula.itens = formula_lines(db, itens)?;
+    formula.save(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).formula(&formula).map_err(|e| e.to_string())
+}
+
+/// Confere as linhas da fórmula: cada item precisa existir e a tolerância
+/// não pode ser negativa.
+fn formula_lines(db: &sled::Db, itens: Vec<FormulaLineInput>) -> Result<Vec<ItemFormula>, String> {
+    itens.into_iter().map(|linha| {
+        let item = Item::get_by_id(&linha.item_id, db).map_err(|e| e.to_string())?
+            .ok_or("Item não encontrado".to_string())?;
+        linha.into_line(&item)
+    }).collect()
 }
 
 #[tauri::command]
-fn create_formula(nome: String, itens: Vec<(String, f64)>) -> Result<models::formula::Formula, String> {
+fn create_user(token: String, username: String, password: String, role: String, sessions: State<'_, SessionStore>) -> Result<models::user::UserInfo, String> {
     let db = models::connect_db();
-    // itens: Vec<(item_id, peso)>
-    let mut resolved_items: Vec<models::item::Item> = Vec::new();
-    for (item_id, _peso) in &itens {
-        let items = Item::get_all(&db, 0, 1000).map_err(|e| e.to_string())?;
-        let found = items.into_iter().find(|it| it.id == *item_id).ok_or("Item não encontrado".to_string())?;
-        resolved_items.push(found);
+    auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    auth::validate_password(&password)?;
+    if models::user::User::find_by_username(&username, db).map_err(|e| e.to_string())?.is_some() {
+        return Err("Nome de usuário já existe".to_string());
     }
-    let mut formula = models::formula::Formula::new(nome, resolved_items);
-    // set pesos
-    for (item_id, peso) in itens {
-        for itf in &mut formula.itens {
-            if itf.item.id == item_id {
-                itf.peso = peso;
-            }
+    let user = models::user::User::new(username, password, models::user::Role::from_name(&role)?).map_err(|e| e.to_string())?;
+    user.save(db).map_err(|e| e.to_string())?;
+    Ok(models::user::UserInfo::from(&user))
+}
+
+#[tauri::command]
+fn search_formulas(token: String, name: String, page: usize, page_size: usize, sessions: State<'_, SessionStore>) -> Result<Page<FormulaView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let formulas = Formula::list_by_name(&name, db, page, page_size).map_err(|e| e.to_string())?;
+    Resolver::new(db).page(formulas, Resolver::formula).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn search_users(token: String, name: String, page: usize, page_size: usize, sessions: State<'_, SessionStore>) -> Result<Page<models::user::UserInfo>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    let users = models::user::User::list_by_name(&name, db, page, page_size).map_err(|e| e.to_string())?;
+    Ok(users.map(|u| models::user::UserInfo::from(&u)))
+}
+
+#[tauri::command]
+fn list_users(token: String, page: usize, page_size: usize, cursor: Option<String>, sort_by: Option<SortField>, direction: Option<SortDirection>, sessions: State<'_, SessionStore>) -> Result<Page<models::user::UserInfo>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    let users = models::user::User::get_all(db, page, page_size, cursor.as_deref(), Sort::from_params(sort_by, direction)).map_err(|e| e.to_string())?;
+    Ok(users.map(|u| models::user::UserInfo::from(&u)))
+}
+
+#[tauri::command]
+fn get_fornecedor_by_id(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<Option<Fornecedor>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    Fornecedor::get_by_id(&id, db).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_fornecedor(token: String, id: String, nome: String, sessions: State<'_, SessionStore>) -> Result<Fornecedor, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
+    let mut fornecedor = Fornecedor::get_by_id(&id, db).map_err(|e| e.to_string())?
+        .ok_or("Fornecedor não encontrado".to_string())?;
+    fornecedor.nome = nome;
+    fornecedor.update(db).map_err(|e| e.to_string())?;
+    Ok(fornecedor)
+}
+
+#[tauri::command]
+fn delete_fornecedor(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<(), CommandError> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
+    let itens = Item::find_by_fornecedor(&id, db).map_err(|e| e.to_string())?;
+    CommandError::check_dependents(
+        "Fornecedor possui itens cadastrados",
+        itens.iter().map(|it| Dependent::new("item", &it.id, &it.nome)).collect(),
+    )?;
+    Fornecedor::delete(&id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "fornecedor", &id).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_item_by_id(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<Option<ItemView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    match Item::get_by_id(&id, db).map_err(|e| e.to_string())? {
+        Some(item) => Resolver::new(db).item(&item).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+fn update_item(token: String, id: String, nome: String, fornecedor_id: String, measure: Option<ItemMeasure>, sessions: State<'_, SessionStore>) -> Result<ItemView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
+    let mut item = Item::get_by_id(&id, db).map_err(|e| e.to_string())?
+        .ok_or("Item não encontrado".to_string())?;
+    if item.fornecedor_id != fornecedor_id {
+        if Fornecedor::get_by_id(&fornecedor_id, db).map_err(|e| e.to_string())?.is_none() {
+            return Err("Fornecedor não encontrado".to_string());
         }
+        item.fornecedor_id = fornecedor_id;
+    }
+    item.nome = nome;
+    // fórmulas já gravadas mantêm o fator com que foram informadas
+    if let Some(measure) = measure {
+        item.set_measure(measure)?;
     }
-    formula.save(&db).map_err(|e| e.to_string())?;
-    Ok(formula)
+    item.update(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).item(&item).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn create_user(username: String, password: String, role: String) -> Result<models::user::User, String> {
+fn delete_item(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<(), CommandError> {
     let db = models::connect_db();
-    let r = match role.to_lowercase().as_str() {
-        "admin" => models::user::Role::Admin,
-        _ => models::user::Role::User,
+    let user = auth::authorize(db, &sessions, &token, Permission::EditCadastros)?;
+    let formulas = Formula::find_by_item(&id, db).map_err(|e| e.to_string())?;
+    CommandError::check_dependents(
+        "Item é usado em fórmulas",
+        formulas.iter().map(|f| Dependent::new("formula", &f.id, &f.nome)).collect(),
+    )?;
+    Item::delete(&id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "item", &id).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_formula_by_id(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<Option<FormulaView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    match Formula::get_by_id(&id, db).map_err(|e| e.to_string())? {
+        Some(formula) => Resolver::new(db).formula(&formula).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+fn update_formula(token: String, id: String, nome: String, itens: Vec<FormulaLineInput>, sessions: State<'_, SessionStore>) -> Result<FormulaView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::EditFormulas)?;
+    let mut formula = Formula::get_by_id(&id, db).map_err(|e| e.to_string())?
+        .ok_or("Fórmula não encontrada".to_string())?;
+    formula.nome = nome;
+    formula.itens = formula_lines(db, itens)?;
+    // grava como nova versão, que precisa ser aprovada novamente
+    formula.update(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).formula(&formula).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_formula_versions(token: String, formula_id: String, sessions: State<'_, SessionStore>) -> Result<Vec<FormulaView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let versions = Formula::list_versions(&formula_id, db).map_err(|e| e.to_string())?;
+    let mut resolver = Resolver::new(db);
+    versions.iter().map(|f| resolver.formula(f).map_err(|e| e.to_string())).collect()
+}
+
+#[tauri::command]
+fn get_formula_version(token: String, formula_id: String, version: u32, sessions: State<'_, SessionStore>) -> Result<Option<FormulaView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    match Formula::get_version(&formula_id, version, db).map_err(|e| e.to_string())? {
+        Some(formula) => Resolver::new(db).formula(&formula).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+fn diff_formula_versions(token: String, formula_id: String, from_version: u32, to_version: u32, sessions: State<'_, SessionStore>) -> Result<models::formula::FormulaDiff, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let mut resolver = Resolver::new(db);
+    let mut load = |version: u32| -> Result<FormulaView, String> {
+        let formula = Formula::get_version(&formula_id, version, db).map_err(|e| e.to_string())?
+            .ok_or(format!("Versão {} da fórmula não encontrada", version))?;
+        resolver.formula(&formula).map_err(|e| e.to_string())
     };
-    let user = models::user::User::new(username, password, r);
-    user.save(&db).map_err(|e| e.to_string())?;
-    Ok(user)
+    let before = load(from_version)?;
+    let after = load(to_version)?;
+    Ok(models::formula::FormulaDiff::between(&before, &after))
 }
 
 #[tauri::command]
-fn search_formulas(name: String, page: usize, page_size: usize) -> Result<Vec<models::formula::Formula>, String> {
+fn delete_formula(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<(), CommandError> {
     let db = models::connect_db();
-    models::formula::Formula::list_by_name(&name, &db, page, page_size).map_err(|e| e.to_string())
+    let user = auth::authorize(db, &sessions, &token, Permission::EditFormulas)?;
+    let processos = Processo::find_open_by_formula(&id, db).map_err(|e| e.to_string())?;
+    CommandError::check_dependents(
+        "Fórmula é usada por processos em aberto",
+        processos.iter().map(|p| Dependent::new("processo", &p.id, &p.nome)).collect(),
+    )?;
+    Formula::delete(&id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "formula", &id).map_err(|e| e.to_string())?;
+    Ok(())
 }
 
 #[tauri::command]
-fn search_users(name: String, page: usize, page_size: usize) -> Result<Vec<models::user::User>, String> {
+fn get_user_by_id(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<Option<models::user::UserInfo>, String> {
     let db = models::connect_db();
-    models::user::User::list_by_name(&name, &db, page, page_size).map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    let user = models::user::User::get_by_id(&id, db).map_err(|e| e.to_string())?;
+    Ok(user.as_ref().map(models::user::UserInfo::from))
 }
 
-#[allow(dead_code)]
 #[tauri::command]
-fn list_users(page: usize, page_size: usize) -> Result<Vec<models::user::User>, String> {
+fn update_user(token: String, id: String, username: String, role: String, password: Option<String>, sessions: State<'_, SessionStore>) -> Result<models::user::UserInfo, String> {
     let db = models::connect_db();
-    models::user::User::get_all(&db, page, page_size).map_err(|e| e.to_string())
+    auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    let mut user = models::user::User::get_by_id(&id, db).map_err(|e| e.to_string())?
+        .ok_or("Usuário não encontrado".to_string())?;
+    if let Some(existing) = models::user::User::find_by_username(&username, db).map_err(|e| e.to_string())? {
+        if existing.id != user.id {
+            return Err("Nome de usuário já existe".to_string());
+        }
+    }
+    let role = models::user::Role::from_name(&role)?;
+    if user.role == models::user::Role::Admin && role != models::user::Role::Admin
+        && models::user::User::count_admins(db).map_err(|e| e.to_string())? <= 1 {
+        return Err("O sistema precisa de ao menos um administrador".to_string());
+    }
+    user.username = username;
+    user.role = role;
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        auth::validate_password(&password)?;
+        user.set_password(&password).map_err(|e| e.to_string())?;
+        // senha definida pelo administrador: o usuário deve trocá-la no próximo login
+        user.must_change_password = true;
+    }
+    user.update(db).map_err(|e| e.to_string())?;
+    Ok(models::user::UserInfo::from(&user))
 }
 
 #[tauri::command]
-fn create_sprint_for_processo(processo_id: String, remaining_sprints: usize, operador_username: String) -> Result<models::sprint::Sprint, String> {
+fn delete_user(token: String, id: String, sessions: State<'_, SessionStore>) -> Result<(), CommandError> {
     let db = models::connect_db();
-    
+    let user = auth::authorize(db, &sessions, &token, Permission::ManageUsers)?;
+    if user.id == id {
+        return Err(CommandError::from("Não é possível excluir o próprio usuário".to_string()));
+    }
+    let alvo = models::user::User::get_by_id(&id, db).map_err(|e| e.to_string())?;
+    if alvo.is_some_and(|u| u.role == models::user::Role::Admin)
+        && models::user::User::count_admins(db).map_err(|e| e.to_string())? <= 1 {
+        return Err(CommandError::from("O sistema precisa de ao menos um administrador".to_string()));
+    }
+    models::user::User::delete(&id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "user", &id).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn create_sprint_for_processo(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+
+    // Operador é sempre o usuário da sessão, nunca um parâmetro do frontend
+    let operador = auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+
     // Busca processo
-    let processo = match get_processo(processo_id.clone())? {
+    let mut processo = match load_processo(db, &processo_id)? {
         Some(p) => p,
         None => return Err("Processo não encontrado".to_string())
     };
-    
-    // Busca operador (por enquanto usa admin, TODO: implementar autenticação)
-    let user_tree = db.open_tree("users").map_err(|e| e.to_string())?;
-    let operador = user_tree.iter()
-        .find_map(|result| {
-            if let Ok((_k, v)) = result {
-                if let Ok(user) = serde_json::from_slice::<models::user::User>(&v) {
-                    if user.username == operador_username {
-                        return Some(user);
-                    }
-                }
-            }
-            None
-        })
-        .ok_or("Operador não encontrado".to_string())?;
-    
+
+    // Só processos ativos recebem sprints; o primeiro tira o processo do planejamento
+    let status = processo.status;
+    processo.start_sprint(&operador).map_err(|e| e.to_string())?;
+
+    // Um rascunho em aberto é retomado em vez de criar outro sprint
+    if let Some(draft) = open_draft(db, &processo)? {
+        return Resolver::new(db).sprint(&draft, &processo.formula).map_err(|e| e.to_string());
+    }
+    if processo.progress().complete {
+        return Err("Todos os sprints planejados já foram concluídos; finalize o processo".to_string());
+    }
+    // a mudança de status só é gravada quando o sprint vai mesmo ser criado
+    if processo.status != status {
+        processo.save(db).map_err(|e| e.to_string())?;
+    }
+
     // Calcula sugestões de peso
-    let suggestions = processo.suggest_next_sprint_targets(remaining_sprints);
+    let suggestions = processo.suggest_next_sprint_targets();
     
     // Cria sprint items com targets sugeridos
-    let sprint_items: Vec<models::sprint::SprintItem> = processo.formula.itens.iter()
+    let sprint_items: Vec<SprintItem> = processo.formula.itens.iter()
         .map(|item_formula| {
-            let target = suggestions.get(&item_formula.item.id).cloned().unwrap_or(item_formula.peso);
-            models::sprint::SprintItem::new(item_formula.item.clone(), target)
+            let (target, rounding) = match suggestions.get(&item_formula.item.id) {
+                Some(s) => (s.target, s.rounding),
+                None => (processo.base_weight(&item_formula.item.id), Mass::ZERO),
+            };
+            SprintItem { tolerance: item_formula.tolerance, rounding, ..SprintItem::new(item_formula.item.id.clone(), target) }
         })
         .collect();
     
-    // Cria sprint
+    // Cria sprint e já grava como rascunho, para sobreviver a uma queda
     let sprint_numero = processo.sprints.len() + 1;
-    let sprint = models::sprint::Sprint::new(processo_id, sprint_numero, sprint_items, operador);
+    let sprint = Sprint::new(processo_id, sprint_numero, sprint_items, operador.id);
+    sprint.save(db).map_err(|e| e.to_string())?;
     
-    Ok(sprint)
+    Resolver::new(db).sprint(&sprint, &processo.formula).map_err(|e| e.to_string())
+}
+
+/// Rascunho em aberto do processo. Um rascunho que já consta no processo
+/// (queda entre gravar o processo e apagar o rascunho) é descartado.
+fn open_draft(db: &sled::Db, processo: &Processo) -> Result<Option<Sprint>, String> {
+    match Sprint::find_open(&processo.id, db).map_err(|e| e.to_string())? {
+        Some(draft) if processo.sprints.iter().any(|s| s.id == draft.id) => {
+            Sprint::delete(&draft.id, db).map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+        other => Ok(other),
+    }
+}
+
+#[tauri::command]
+fn get_open_sprint(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<Option<SprintView>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    let processo = load_processo(db, &processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    match open_draft(db, &processo)? {
+        Some(draft) => Resolver::new(db).sprint(&draft, &processo.formula).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Grava o peso de um item, na unidade da linha, no rascunho assim que ele
+/// é informado.
+#[tauri::command]
+fn set_sprint_actual(token: String, sprint_id: String, item_id: String, actual: f64, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    weigh(db, &sprint_id, |processo, sprint| {
+        let actual = quantity_in_kg(processo, &item_id, actual)?;
+        sprint.record_actual(&item_id, actual).map_err(|e| e.to_string())
+    })
+}
+
+/// Lança mais uma porção de um item, na unidade da linha.
+#[tauri::command]
+fn add_sprint_addition(token: String, sprint_id: String, item_id: String, quantity: f64, lot: Option<String>, scale_id: Option<String>, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    weigh(db, &sprint_id, |processo, sprint| {
+        let quantity = quantity_in_kg(processo, &item_id, quantity)?;
+        sprint.add_addition(&item_id, Addition::new(quantity, lot, scale_id)).map_err(|e| e.to_string())
+    })
 }
 
+/// Lança como porção o último peso estável da balança, já em kg.
 #[tauri::command]
-fn save_sprint_to_processo(processo_id: String, sprint: models::sprint::Sprint) -> Result<(), String> {
+fn add_sprint_addition_from_scale(token: String, sprint_id: String, item_id: String, scale_id: String, lot: Option<String>, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
     let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    let quantity = scales.stable_weight(&scale_id)?;
+    if quantity <= Mass::ZERO {
+        return Err("Peso da balança deve ser maior que zero".to_string());
+    }
+    weigh(db, &sprint_id, |_, sprint| {
+        sprint.add_addition(&item_id, Addition::new(quantity, lot, Some(scale_id))).map_err(|e| e.to_string())
+    })
+}
+
+/// Registra que o item não será dosado; conta como falta de todo o peso base.
+#[tauri::command]
+fn skip_sprint_item(token: String, sprint_id: String, item_id: String, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    weigh(db, &sprint_id, |_, sprint| {
+        sprint.skip_item(&item_id).map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+fn remove_sprint_addition(token: String, sprint_id: String, item_id: String, addition_id: String, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    weigh(db, &sprint_id, |_, sprint| {
+        sprint.remove_addition(&item_id, &addition_id).map_err(|e| e.to_string())
+    })
+}
+
+/// Altera os pesos de um sprint em aberto de um processo ativo e reajusta
+/// os alvos quando o processo mantém a proporção.
+fn weigh(db: &sled::Db, sprint_id: &str, change: impl FnOnce(&Processo, &mut Sprint) -> Result<(), String>) -> Result<SprintView, String> {
+    let mut sprint = Sprint::get_by_id(sprint_id, db).map_err(|e| e.to_string())?
+        .ok_or("Sprint não encontrado".to_string())?;
+    let processo = load_processo(db, &sprint.processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    if !processo.status.is_active() {
+        return Err(format!("Processo {:?} não aceita pesagens", processo.status));
+    }
+    change(&processo, &mut sprint)?;
+    processo.rebalance(&mut sprint);
+    sprint.save(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).sprint(&sprint, &processo.formula).map_err(|e| e.to_string())
+}
+
+/// O operador informa na unidade da linha; o sprint guarda em mg inteiros.
+fn quantity_in_kg(processo: &Processo, item_id: &str, quantity: f64) -> Result<Mass, String> {
+    if !quantity.is_finite() || quantity < 0.0 {
+        return Err("Peso inválido".to_string());
+    }
+    Ok(Mass::from_kg(processo.formula.to_kg(item_id, quantity)))
+}
+
+/// Conclui o rascunho gravado do sprint, com os pesos lançados nele. Itens
+/// sem peso só são aceitos com `skip_missing`, depois de o operador
+/// confirmar que não foram dosados. Devolve o andamento do processo.
+#[tauri::command]
+fn save_sprint_to_processo(token: String, processo_id: String, sprint: Sprint, skip_missing: Option<bool>, sessions: State<'_, SessionStore>) -> Result<ProcessoProgress, String> {
+    let db = models::connect_db();
+    let operador = auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
     
     // Busca processo
-    let mut processo = match get_processo(processo_id)? {
+    let mut processo = match load_processo(db, &processo_id)? {
         Some(p) => p,
         None => return Err("Processo não encontrado".to_string())
     };
+    if processo.sprints.iter().any(|s| s.id == sprint.id) {
+        return Err("Sprint já foi salvo neste processo".to_string());
+    }
+    processo.start_sprint(&operador).map_err(|e| e.to_string())?;
+
+    // Conclui o rascunho gravado deste processo; do payload só vem o comentário
+    let mut concluido = match Sprint::get_by_id(&sprint.id, db).map_err(|e| e.to_string())? {
+        Some(draft) if draft.processo_id != processo_id => return Err("Sprint não pertence a este processo".to_string()),
+        Some(draft) if draft.is_open() => draft,
+        Some(_) => return Err("Sprint já foi concluído".to_string()),
+        None => return Err("Rascunho do sprint não encontrado".to_string()),
+    };
+    concluido.complete(sprint.comentario.clone(), skip_missing.unwrap_or(false)).map_err(|e| e.to_string())?;
     
+    // O operador registrado é quem está logado ao salvar, não o que veio no payload
+    concluido.operador_id = operador.id;
+
     // Adiciona sprint ao processo
-    processo.add_sprint(sprint);
+    let sprint_id = concluido.id.clone();
+    processo.add_sprint(concluido);
     
-    // Salva processo atualizado
-    processo.save(&db).map_err(|e| e.to_string())?;
+    // Salva processo atualizado e só então descarta o rascunho
+    processo.save(db).map_err(|e| e.to_string())?;
+    Sprint::delete(&sprint_id, db).map_err(|e| e.to_string())?;
     
-    Ok(())
+    Ok(processo.progress())
+}
+
+/// Libera a conclusão de um sprint com itens fora da tolerância.
+#[tauri::command]
+fn override_sprint_tolerance(token: String, sprint_id: String, justification: String, sessions: State<'_, SessionStore>) -> Result<SprintView, String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::OverrideTolerances)?;
+    let mut sprint = Sprint::get_by_id(&sprint_id, db).map_err(|e| e.to_string())?
+        .ok_or("Sprint não encontrado".to_string())?;
+    let processo = load_processo(db, &sprint.processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    if !processo.status.is_active() {
+        return Err(format!("Processo {:?} não aceita pesagens", processo.status));
+    }
+    sprint.override_tolerance(&user, &justification).map_err(|e| e.to_string())?;
+    sprint.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "override_tolerance", "sprint", &sprint.id).map_err(|e| e.to_string())?;
+    Resolver::new(db).sprint(&sprint, &processo.formula).map_err(|e| e.to_string())
+}
+
+/// Abandona o sprint em aberto. Ele fica no histórico do processo como
+/// abortado e o próximo sprint parte das mesmas sugestões.
+#[tauri::command]
+fn abort_sprint(token: String, sprint_id: String, reason: Option<String>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    let sprint = Sprint::get_by_id(&sprint_id, db).map_err(|e| e.to_string())?
+        .ok_or("Sprint não encontrado".to_string())?;
+    let mut processo = load_processo(db, &sprint.processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    processo.abort_sprint(sprint, &user.id, reason).map_err(|e| e.to_string())?;
+    processo.save(db).map_err(|e| e.to_string())?;
+    Sprint::delete(&sprint_id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "abort", "sprint", &sprint_id).map_err(|e| e.to_string())
 }
 
+/// Anula um sprint concluído, mantendo o registro no histórico.
 #[tauri::command]
-fn finalize_processo(processo_id: String) -> Result<(), String> {
+fn void_sprint(token: String, processo_id: String, sprint_id: String, reason: String, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
     let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::VoidSprints)?;
+    let mut processo = load_processo(db, &processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    // o fechamento já foi conferido e assinado sobre estes sprints
+    if processo.status.is_closed() {
+        return Err(format!("Processo {:?} não pode ser alterado", processo.status));
+    }
+    processo.sprint_mut(&sprint_id)
+        .ok_or("Sprint não encontrado neste processo".to_string())?
+        .void(&user.id, &reason)
+        .map_err(|e| e.to_string())?;
+    processo.touch();
+    processo.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "void", "sprint", &sprint_id).map_err(|e| e.to_string())?;
+    Resolver::new(db).processo(&processo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn finalize_processo(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::ManageProcessos)?;
     
     // Busca processo
-    let mut processo = match get_processo(processo_id)? {
+    let mut processo = match load_processo(db, &processo_id)? {
         Some(p) => p,
         None => return Err("Processo não encontrado".to_string())
     };
+
+    // Um sprint ainda em pesagem ficaria fora do fechamento
+    if open_draft(db, &processo)?.is_some() {
+        return Err("Há um sprint em aberto; conclua ou aborte antes de finalizar".to_string());
+    }
     
-    // Atualiza status para terminado
-    processo.update_status("Terminado".to_string());
+    // Atualiza status para finalizado
+    processo.transition(ProcessoStatus::Finished, &user, None).map_err(|e| e.to_string())?;
+    processo.reconciliation = Some(Reconciliation::build(&processo));
     
     // Salva processo
-    processo.save(&db).map_err(|e| e.to_string())?;
+    processo.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "finalize", "processo", &processo.id).map_err(|e| e.to_string())?;
     
     Ok(())
 }
 
+/// Fechamento do lote: o gravado ao finalizar ou, com o processo ainda em
+/// andamento, uma prévia calculada na hora.
+#[tauri::command]
+fn get_processo_reconciliation(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<Reconciliation, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ViewReports)?;
+    let processo = load_processo(db, &processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    Ok(processo.reconciliation.clone().unwrap_or_else(|| Reconciliation::build(&processo)))
+}
+
+/// Aprovação do lote pela qualidade.
+#[tauri::command]
+fn sign_off_processo(token: String, processo_id: String, comment: Option<String>, sessions: State<'_, SessionStore>) -> Result<Reconciliation, String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::SignOffBatches)?;
+    let mut processo = load_processo(db, &processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    let reconciliation = processo.reconciliation.as_mut()
+        .ok_or("Processo ainda não foi finalizado".to_string())?;
+    reconciliation.sign_off(&user, comment).map_err(|e| e.to_string())?;
+    let reconciliation = reconciliation.clone();
+    processo.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "sign_off", "processo", &processo.id).map_err(|e| e.to_string())?;
+    Ok(reconciliation)
+}
+
+/// Troca a estratégia de compensação e a capacidade do misturador; vale a
+/// partir do próximo peso informado.
 #[tauri::command]
-fn delete_processo(processo_id: String) -> Result<(), String> {
+fn set_processo_compensation(token: String, processo_id: String, compensation: CompensationStrategy, mixer_capacity: Option<f64>, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
     let db = models::connect_db();
-    Processo::delete(&processo_id, &db).map_err(|e| e.to_string())
+    let user = auth::authorize(db, &sessions, &token, Permission::ManageProcessos)?;
+    compensation.validate()?;
+    let mut processo = load_processo(db, &processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    if processo.status.is_closed() {
+        return Err(format!("Processo {:?} não pode ser alterado", processo.status));
+    }
+    processo.compensation = compensation;
+    processo.set_mixer_capacity(mixer_capacity).map_err(|e| e.to_string())?;
+    processo.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "set_compensation", "processo", &processo.id).map_err(|e| e.to_string())?;
+    Resolver::new(db).processo(&processo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn pause_processo(token: String, processo_id: String, reason: String, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
+    change_processo_status(&token, &processo_id, ProcessoStatus::Paused, Some(reason), &sessions)
 }
 
 #[tauri::command]
-fn clear_processo_sprints(processo_id: String) -> Result<(), String> {
+fn resume_processo(token: String, processo_id: String, reason: Option<String>, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
+    change_processo_status(&token, &processo_id, ProcessoStatus::InProgress, reason, &sessions)
+}
+
+#[tauri::command]
+fn cancel_processo(token: String, processo_id: String, reason: String, sessions: State<'_, SessionStore>) -> Result<ProcessoView, String> {
+    change_processo_status(&token, &processo_id, ProcessoStatus::Cancelled, Some(reason), &sessions)
+}
+
+/// Aplica uma transição de situação do processo. Ao cancelar, o sprint em
+/// aberto é abortado com o mesmo motivo.
+fn change_processo_status(token: &str, processo_id: &str, to: ProcessoStatus, reason: Option<String>, sessions: &SessionStore) -> Result<ProcessoView, String> {
     let db = models::connect_db();
-    let mut processo = match get_processo(processo_id)? {
+    let user = auth::authorize(db, sessions, token, Permission::ManageProcessos)?;
+    let mut processo = load_processo(db, processo_id)?.ok_or("Processo não encontrado".to_string())?;
+    processo.transition(to, &user, reason.clone()).map_err(|e| e.to_string())?;
+
+    let mut abortado = None;
+    if to == ProcessoStatus::Cancelled {
+        if let Some(mut draft) = open_draft(db, &processo)? {
+            draft.abort(&user.id, reason).map_err(|e| e.to_string())?;
+            abortado = Some(draft.id.clone());
+            processo.add_sprint(draft);
+        }
+    }
+    processo.save(db).map_err(|e| e.to_string())?;
+    if let Some(id) = abortado {
+        Sprint::delete(&id, db).map_err(|e| e.to_string())?;
+    }
+
+    let action = match to {
+        ProcessoStatus::Paused => "pause",
+        ProcessoStatus::Cancelled => "cancel",
+        _ => "resume",
+    };
+    AuditEntry::record(db, &user, action, "processo", &processo.id).map_err(|e| e.to_string())?;
+    Resolver::new(db).processo(&processo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_processo(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::DeleteRecords)?;
+    for draft in Sprint::list_by_processo(&processo_id, db).map_err(|e| e.to_string())? {
+        Sprint::delete(&draft.id, db).map_err(|e| e.to_string())?;
+    }
+    Processo::delete(&processo_id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "processo", &processo_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn clear_processo_sprints(token: String, processo_id: String, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::DeleteRecords)?;
+    let mut processo = match load_processo(db, &processo_id)? {
         Some(p) => p,
         None => return Err("Processo não encontrado".to_string())
     };
     processo.sprints.clear();
     processo.touch();
-    processo.save(&db).map_err(|e| e.to_string())?;
+    for draft in Sprint::list_by_processo(&processo.id, db).map_err(|e| e.to_string())? {
+        Sprint::delete(&draft.id, db).map_err(|e| e.to_string())?;
+    }
+    processo.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "clear_sprints", "processo", &processo.id).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn approve_formula(token: String, formula_id: String, sessions: State<'_, SessionStore>) -> Result<FormulaView, String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::ApproveFormulas)?;
+    let mut formula = Formula::get_by_id(&formula_id, db).map_err(|e| e.to_string())?
+        .ok_or("Fórmula não encontrada".to_string())?;
+    formula.approve(&user.id);
+    formula.save(db).map_err(|e| e.to_string())?;
+    Resolver::new(db).formula(&formula).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_audit_entries(token: String, entity_id: String, sessions: State<'_, SessionStore>) -> Result<Vec<AuditEntry>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ViewReports)?;
+    AuditEntry::list_by_entity(&entity_id, db).map_err(|e| e.to_string())
+}
+
+/// Publica os eventos das balanças para o frontend.
+fn scale_events(app: AppHandle) -> impl Fn(ScaleEvent) + Clone + Send + 'static {
+    move |event| {
+        let _ = app.emit(SCALE_EVENT, event);
+    }
+}
+
+/// Grava a balança na estação e passa a mantê-la conectada; as leituras
+/// chegam ao frontend no evento `scale-event`.
+#[tauri::command]
+fn save_scale(token: String, config: ScaleConfig, app: AppHandle, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::ManageScales)?;
+    if config.id == SIMULATOR_ID {
+        return Err("Identificação reservada ao simulador".to_string());
+    }
+    config.validate()?;
+    config.save(db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "update", "scale", &config.id).map_err(|e| e.to_string())?;
+    scales.connect(config, scale_events(app))
+}
+
+#[tauri::command]
+fn delete_scale(token: String, scale_id: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    let user = auth::authorize(db, &sessions, &token, Permission::ManageScales)?;
+    ScaleConfig::delete(&scale_id, db).map_err(|e| e.to_string())?;
+    AuditEntry::record(db, &user, "delete", "scale", &scale_id).map_err(|e| e.to_string())?;
+    // pode não estar conectada
+    let _ = scales.disconnect(&scale_id);
+    Ok(())
+}
+
+/// Tenta reconectar agora, sem esperar o intervalo de nova tentativa.
+#[tauri::command]
+fn reconnect_scale(token: String, scale_id: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    scales.send(&scale_id, Command::Reconnect)
+}
+
+/// Liga o simulador de treinamento e conecta a balança "simulador" a ele.
+#[tauri::command]
+fn start_scale_simulator(token: String, protocol: ProtocolKind, port: Option<u16>, app: AppHandle, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<ScaleConfig, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ManageScales)?;
+    scales.start_simulator(protocol, port.unwrap_or(0), scale_events(app))
+}
+
+/// Peso no prato do simulador, em kg.
+#[tauri::command]
+fn set_scale_simulator_load(token: String, load: f64, stable: bool, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    if !load.is_finite() {
+        return Err("Peso inválido".to_string());
+    }
+    scales.set_simulator_load(Mass::from_kg(load), stable)
+}
+
+#[tauri::command]
+fn stop_scale_simulator(token: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::ManageScales)?;
+    scales.stop_simulator();
     Ok(())
 }
 
+#[tauri::command]
+fn list_scales(token: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<Vec<ScaleStatus>, String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::View)?;
+    Ok(scales.list())
+}
+
+#[tauri::command]
+fn tare_scale(token: String, scale_id: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    scales.send(&scale_id, Command::Tare)
+}
+
+#[tauri::command]
+fn zero_scale(token: String, scale_id: String, scales: State<'_, ScaleManager>, sessions: State<'_, SessionStore>) -> Result<(), String> {
+    let db = models::connect_db();
+    auth::authorize(db, &sessions, &token, Permission::OperateSprints)?;
+    scales.send(&scale_id, Command::Zero)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // inicializa DB e cria admin se necessário
+    // inicializa DB, migra bancos antigos (massas em mg, índices) e cria admin se necessário
     let db = crate::models::connect_db();
-    if let Err(e) = crate::models::create_adm_if_not_exists(&db) {
+    if let Err(e) = crate::models::quantity::ensure_mass_in_mg(db) {
+        eprintln!("failed to convert stored masses to mg: {}", e);
+    }
+    if let Err(e) = crate::models::index::ensure_indexes(db) {
+        eprintln!("failed to rebuild indexes: {}", e);
+    }
+    if let Err(e) = crate::models::create_adm_if_not_exists(db) {
         eprintln!("failed to ensure admin user: {}", e);
     }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(SessionStore::default())
+        .manage(ScaleManager::default())
+        .setup(|app| {
+            // balanças configuradas nesta estação
+            let handle = app.handle().clone();
+            app.state::<ScaleManager>().connect_all(db, scale_events(handle));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             check_trial_status,
             get_trial_info,
+            login,
+            logout,
+            change_password,
             greet,
             list_processos,
             get_processo,
@@ -292,8 +980,23 @@ pub fn run() {
             list_itens,
             suggest_sprint_targets,
             create_sprint_for_processo,
+            get_open_sprint,
+            set_sprint_actual,
+            add_sprint_addition,
+            remove_sprint_addition,
+            add_sprint_addition_from_scale,
+            skip_sprint_item,
             save_sprint_to_processo,
+            override_sprint_tolerance,
+            abort_sprint,
+            void_sprint,
             finalize_processo,
+            get_processo_reconciliation,
+            sign_off_processo,
+            set_processo_compensation,
+            pause_processo,
+            resume_processo,
+            cancel_processo,
             delete_processo,
             clear_processo_sprints,
             create_fornecedor,
@@ -303,8 +1006,36 @@ pub fn run() {
             create_user,
             search_fornecedores,
             search_itens,
+            search_processos,
             search_formulas,
-            search_users
+            search_users,
+            list_users,
+            list_audit_entries,
+            approve_formula,
+            get_fornecedor_by_id,
+            update_fornecedor,
+            delete_fornecedor,
+            get_item_by_id,
+            update_item,
+            delete_item,
+            get_formula_by_id,
+            update_formula,
+            list_formula_versions,
+            get_formula_version,
+            diff_formula_versions,
+            delete_formula,
+            get_user_by_id,
+            update_user,
+            delete_user,
+            save_scale,
+            delete_scale,
+            list_scales,
+            reconnect_scale,
+            tare_scale,
+            zero_scale,
+            start_scale_simulator,
+            set_scale_simulator_load,
+            stop_scale_simulator
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
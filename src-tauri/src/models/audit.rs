@@ -0,0 +1,57 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use uuid;
+use crate::models::user::User;
+
+/// Registro de quem executou uma operação sensível (finalização, exclusões).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub id: String,
+    pub user_id: String,
+    pub username: String,
+    pub action: String,
+    pub entity: String,
+    pub entity_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[allow(dead_code)]
+impl AuditEntry {
+    pub fn new(user: &User, action: &str, entity: &str, entity_id: &str) -> Self {
+        AuditEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            action: action.to_string(),
+            entity: entity.to_string(),
+            entity_id: entity_id.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Cria e persiste um registro de auditoria.
+    pub fn record(db: &sled::Db, user: &User, action: &str, entity: &str, entity_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        AuditEntry::new(user, action, entity, entity_id).save(db)
+    }
+
+    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        let tree = db.open_tree("audit_log")?;
+        let serialized = serde_json::to_vec(self)?;
+        tree.insert(self.id.as_bytes(), serialized)?;
+        Ok(())
+    }
+
+    pub fn list_by_entity(entity_id: &str, db: &sled::Db) -> Result<Vec<AuditEntry>, Box<dyn std::error::Error>> {
+        let tree = db.open_tree("audit_log")?;
+        let mut entries = Vec::new();
+        for result in tree.iter() {
+            let (_k, value) = result?;
+            let entry: AuditEntry = serde_json::from_slice(&value)?;
+            if entry.entity_id == entity_id {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(|e| e.created_at);
+        Ok(entries)
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::models::formula::ItemProporcao;
+
+/// Como o erro acumulado de um item é devolvido nos sprints seguintes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CompensationStrategy {
+    /// Corrige todo o erro acumulado já no próximo sprint.
+    #[default]
+    FullCarry,
+    /// Divide o erro igualmente entre os sprints que faltam.
+    Spread,
+    /// Corrige no máximo `max_pct` % do peso base por sprint; o resto fica
+    /// para os sprints seguintes.
+    Capped { max_pct: f64 },
+    /// Não compensa; todo sprint parte do peso base.
+    None,
+    /// Mantém cada sprint na proporção da fórmula: uma sobredosagem eleva os
+    /// demais itens do próprio sprint, dentro da capacidade do misturador.
+    /// Nada é levado para os sprints seguintes.
+    RatioPreserving,
+}
+
+impl CompensationStrategy {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            CompensationStrategy::Capped { max_pct } if !max_pct.is_finite() || *max_pct <= 0.0 => {
+                Err("Limite de correção inválido".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Quanto subtrair do peso base no próximo sprint, dado o erro acumulado
+    /// do item e quantos sprints ainda faltam (incluindo o próximo).
+    pub fn correction(&self, accumulated_error: f64, base_weight: f64, remaining_sprints: usize) -> f64 {
+        match self {
+            CompensationStrategy::FullCarry => accumulated_error,
+            CompensationStrategy::Spread => accumulated_error / remaining_sprints.max(1) as f64,
+            CompensationStrategy::Capped { max_pct } => {
+                let cap = base_weight.abs() * max_pct / 100.0;
+                accumulated_error.clamp(-cap, cap)
+            }
+            CompensationStrategy::None | CompensationStrategy::RatioPreserving => 0.0,
+        }
+    }
+}
+
+/// Resultado do reajuste proporcional de um sprint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RatioAdjustment {
+    /// Massa total do sprint / massa planejada.
+    pub factor: f64,
+    pub total_mass: f64,
+    /// Massa além da planejada que o sprint passa a ter.
+    pub extra_mass: f64,
+    /// A capacidade do misturador impediu manter a proporção por completo.
+    pub capped: bool,
+}
+
+/// Alvos que mantêm o sprint na proporção da fórmula dados os pesos já
+/// informados. A massa só cresce: a maior sobredosagem relativa define a
+/// massa do sprint, limitada à capacidade do misturador.
+pub fn ratio_targets(
+    proportions: &[ItemProporcao],
+    planned_mass: f64,
+    actuals: &HashMap<String, f64>,
+    capacity: Option<f64>,
+) -> (RatioAdjustment, HashMap<String, f64>) {
+    let required = proportions.iter()
+        .filter(|p| p.proporcao > 0.0)
+        .filter_map(|p| actuals.get(&p.item_id).map(|a| a / p.proporcao))
+        .fold(planned_mass, f64::max);
+    let (total_mass, capped) = match capacity {
+        Some(cap) if required > cap => (cap.max(planned_mass), true),
+        _ => (required, false),
+    };
+    let targets = proportions.iter()
+        .map(|p| (p.item_id.clone(), p.proporcao * total_mass))
+        .collect();
+    let factor = if planned_mass > 0.0 { total_mass / planned_mass } else { 1.0 };
+    (RatioAdjustment { factor, total_mass, extra_mass: total_mass - planned_mass, capped }, targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correction_per_strategy() {
+        assert_eq!(CompensationStrategy::FullCarry.correction(1.5, 30.0, 3), 1.5);
+        assert_eq!(CompensationStrategy::Spread.correction(1.5, 30.0, 3), 0.5);
+        // sem sprints restantes informados, corrige tudo no próximo
+        assert_eq!(CompensationStrategy::Spread.correction(1.5, 30.0, 0), 1.5);
+        let capped = CompensationStrategy::Capped { max_pct: 2.0 };
+        assert!((capped.correction(1.5, 30.0, 3) - 0.6).abs() < 1e-9);
+        assert!((capped.correction(-1.0, 20.0, 3) + 0.4).abs() < 1e-9);
+        assert_eq!(capped.correction(0.1, 30.0, 3), 0.1);
+        assert_eq!(CompensationStrategy::None.correction(1.5, 30.0, 3), 0.0);
+
+        assert!(CompensationStrategy::Capped { max_pct: 0.0 }.validate().is_err());
+        let json = serde_json::to_value(capped).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "capped", "max_pct": 2.0 }));
+    }
+
+    #[test]
+    fn test_ratio_targets_scale_up_to_overdose() {
+        // A=30, B=20: 60% / 40% de 50 kg
+        let proportions = vec![
+            ItemProporcao { item_id: "a".to_string(), proporcao: 0.6 },
+            ItemProporcao { item_id: "b".to_string(), proporcao: 0.4 },
+        ];
+        let mut actuals = HashMap::new();
+        actuals.insert("a".to_string(), 31.5);
+
+        let (adj, targets) = ratio_targets(&proportions, 50.0, &actuals, None);
+        assert!((adj.total_mass - 52.5).abs() < 1e-9);
+        assert!((adj.extra_mass - 2.5).abs() < 1e-9);
+        assert!((adj.factor - 1.05).abs() < 1e-9);
+        assert!(!adj.capped);
+        assert!((targets["a"] - 31.5).abs() < 1e-9);
+        assert!((targets["b"] - 21.0).abs() < 1e-9);
+
+        // subdosagem não reduz o sprint
+        actuals.insert("a".to_string(), 29.0);
+        let (adj, targets) = ratio_targets(&proportions, 50.0, &actuals, None);
+        assert_eq!(adj.total_mass, 50.0);
+        assert!((targets["b"] - 20.0).abs() < 1e-9);
+
+        // o misturador comporta só 51 kg
+        actuals.insert("a".to_string(), 31.5);
+        let (adj, targets) = ratio_targets(&proportions, 50.0, &actuals, Some(51.0));
+        assert!(adj.capped);
+        assert_eq!(adj.total_mass, 51.0);
+        assert!((targets["b"] - 20.4).abs() < 1e-9);
+    }
+}
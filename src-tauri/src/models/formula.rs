@@ -1,21 +1,138 @@
 use serde::{Serialize, Deserialize};
 use uuid;
-use crate::models::item::Item;
 use chrono::{DateTime, Utc};
 use crate::models::auditable::Auditable; 
+use crate::models::index::{self, Extra, Indexed, IndexEntry};
+use crate::models::page::{Page, Sort, SortField};
+use crate::models::search;
+use crate::models::resolver::{deserialize_ref, FormulaView};
+use crate::models::item::Item;
+use crate::models::unit::{self, Unit};
 
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ItemFormula{
-    pub item: Item,
+    // bancos antigos gravavam o item inteiro no campo `item`
+    #[serde(alias = "item", deserialize_with = "deserialize_ref")]
+    pub item_id: String,
+    /// Peso por sprint, em kg.
     pub peso: f64,
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    /// Unidade em que a linha foi informada e é exibida.
+    #[serde(default)]
+    pub unit: Unit,
+    /// kg por unidade da linha, fixado quando a linha foi informada.
+    #[serde(default = "unit::kg_default_factor")]
+    pub factor: f64,
+    #[serde(default)]
+    pub limits: DoseLimits,
 }
 
 
 impl ItemFormula {
-    pub fn new(item: Item, peso: f64) -> Self {
-        ItemFormula { item, peso }
+    pub fn new(item_id: String, peso: f64) -> Self {
+        ItemFormula { item_id, peso, tolerance: Tolerance::default(), unit: Unit::Kg, factor: 1.0, limits: DoseLimits::default() }
+    }
+}
+
+/// Linha de fórmula como informada na tela: peso e tolerância absoluta na
+/// unidade escolhida (sem unidade, a do item).
+#[derive(Deserialize, Debug, Clone)]
+pub struct FormulaLineInput {
+    pub item_id: String,
+    pub peso: f64,
+    #[serde(default)]
+    pub unit: Option<Unit>,
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    #[serde(default)]
+    pub limits: DoseLimits,
+}
+
+impl FormulaLineInput {
+    /// Converte a linha para kg.
+    pub fn into_line(self, item: &Item) -> Result<ItemFormula, String> {
+        if !self.peso.is_finite() || self.peso <= 0.0 {
+            return Err("Peso da linha deve ser maior que zero".to_string());
+        }
+        self.tolerance.validate()?;
+        self.limits.validate()?;
+        let unit = self.unit.unwrap_or(item.unit);
+        let factor = item.kg_factor(unit)?;
+        Ok(ItemFormula {
+            item_id: self.item_id,
+            peso: self.peso * factor,
+            tolerance: Tolerance { abs: self.tolerance.abs.map(|a| a * factor), pct: self.tolerance.pct },
+            unit,
+            factor,
+            limits: DoseLimits { min: self.limits.min.map(|m| m * factor), max: self.limits.max.map(|m| m * factor) },
+        })
+    }
+}
+
+/// Desvio aceito na dosagem de uma linha, em kg (`abs`) e/ou em percentual
+/// do alvo (`pct`). Com os dois informados vale o mais restritivo; sem
+/// nenhum, a linha não tem restrição.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Tolerance {
+    #[serde(default)]
+    pub abs: Option<f64>,
+    #[serde(default)]
+    pub pct: Option<f64>,
+}
+
+impl Tolerance {
+    pub fn validate(&self) -> Result<(), String> {
+        for value in [self.abs, self.pct].into_iter().flatten() {
+            if !value.is_finite() || value < 0.0 {
+                return Err("Tolerância inválida".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Desvio máximo, em kg, para o alvo informado.
+    pub fn allowed(&self, target: f64) -> Option<f64> {
+        let pct = self.pct.map(|p| target.abs() * p / 100.0);
+        match (self.abs, pct) {
+            (Some(a), Some(p)) => Some(a.min(p)),
+            (a, p) => a.or(p),
+        }
+    }
+
+    pub fn accepts(&self, target: f64, actual: f64) -> bool {
+        match self.allowed(target) {
+            // folga para não reprovar por arredondamento de ponto flutuante
+            Some(max) => (actual - target).abs() <= max + 1e-9,
+            None => true,
+        }
+    }
+}
+
+/// Dose mínima prática (não dá para dosar 30 g de um produto em saco de
+/// 25 kg) e máxima por sprint (limite do misturador ou da receita), em kg.
+/// A compensação respeita os dois e adia o que não couber.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct DoseLimits {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+impl DoseLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        for value in [self.min, self.max].into_iter().flatten() {
+            if !value.is_finite() || value < 0.0 {
+                return Err("Dose mínima/máxima inválida".to_string());
+            }
+        }
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => Err("Dose mínima maior que a máxima".to_string()),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -25,7 +142,7 @@ impl ItemFormula {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 
 pub struct ItemProporcao {
-    pub item: Item,
+    pub item_id: String,
     pub proporcao: f64,
 }
 
@@ -35,75 +152,164 @@ pub struct Formula {
     pub id: String,
     pub nome: String,
     pub itens: Vec<ItemFormula>,
+    /// Versão atual; cada edição grava uma nova versão e as anteriores ficam
+    /// guardadas, somente leitura, na árvore `formula_versions`.
+    #[serde(default = "first_version")]
+    pub version: u32,
+    /// Usuário (id) que aprovou a fórmula; alterações posteriores exigem nova aprovação.
+    #[serde(default)]
+    pub approved_by: Option<String>,
+    #[serde(default)]
+    pub approved_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+fn first_version() -> u32 {
+    1
+}
+
+/// Versões guardadas, somente leitura, e o índice item → fórmula delas.
+const VERSIONS: &str = "formula_versions";
+const VERSIONS_BY_ITEM: &str = "formula_versions_by_item";
+
+/// Chave de uma versão na árvore `formula_versions`: id, separador e versão
+/// em big-endian, para que as versões de uma fórmula fiquem em ordem.
+fn version_key(id: &str, version: u32) -> Vec<u8> {
+    let mut key = version_prefix(id);
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+/// Chave do índice por item das versões: item, separador e fórmula.
+fn version_item_key(item_id: &str, formula_id: &str) -> Vec<u8> {
+    IndexEntry::multi(VERSIONS_BY_ITEM, item_id, formula_id).key
+}
+
+fn version_prefix(id: &str) -> Vec<u8> {
+    let mut key = id.as_bytes().to_vec();
+    key.push(0);
+    key
+}
+
 #[allow(dead_code)]
 impl Formula {
-    pub fn new(nome: String, itens: Vec<Item>) -> Self {
+    pub fn new(nome: String, item_ids: Vec<String>) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
         let now = Utc::now();
-        let itens = itens.into_iter().map(|item| ItemFormula::new(item, 0.0)).collect();
-        Formula { id, nome, itens, created_at: now, updated_at: now }
+        let itens = item_ids.into_iter().map(|item_id| ItemFormula::new(item_id, 0.0)).collect();
+        Formula { id, nome, itens, version: 1, approved_by: None, approved_at: None, created_at: now, updated_at: now }
     }
 
+    /// Grava a versão atual. Só metadados (como a aprovação) podem mudar sem
+    /// nova versão; alterações de conteúdo passam por `update`.
     pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("formulas")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-        Ok(())
+        // a versão, seu índice por item e o registro atual são gravados juntos
+        let mut extra = vec![Extra::Insert(VERSIONS, version_key(&self.id, self.version), serde_json::to_vec(self)?)];
+        extra.extend(self.itens.iter().map(|it| Extra::Insert(VERSIONS_BY_ITEM, version_item_key(&it.item_id, &self.id), self.id.as_bytes().to_vec())));
+        index::save_with(db, self, &extra)
     }
 
+    /// Cria a versão N+1 com o conteúdo editado; a versão N continua guardada.
     pub fn update(&mut self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        self.version += 1;
+        self.approved_by = None;
+        self.approved_at = None;
         self.touch();
-        let tree = db.open_tree("formulas")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-        Ok(())
+        self.save(db)
     }
 
-    pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("formulas")?;
-        tree.remove(id.as_bytes())?;
-        Ok(())
+    /// Uma versão específica da fórmula.
+    pub fn get_version(id: &str, version: u32, db: &sled::Db) -> Result<Option<Formula>, Box<dyn std::error::Error>> {
+        let versions = db.open_tree(VERSIONS)?;
+        if let Some(bytes) = versions.get(version_key(id, version))? {
+            return Ok(Some(serde_json::from_slice(&bytes)?));
+        }
+        // fórmulas gravadas antes do versionamento só têm a versão atual
+        Ok(Formula::get_by_id(id, db)?.filter(|f| f.version == version))
     }
 
-    pub fn get_all_paginated(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Formula>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("formulas")?;
+    /// Todas as versões guardadas, da mais antiga para a mais nova.
+    pub fn list_versions(id: &str, db: &sled::Db) -> Result<Vec<Formula>, Box<dyn std::error::Error>> {
+        let versions = db.open_tree(VERSIONS)?;
         let mut formulas = Vec::new();
-        let start = page * page_size;
-        for result in tree.iter().skip(start).take(page_size) {
+        for result in versions.scan_prefix(version_prefix(id)) {
             let (_k, value) = result?;
-            let formula: Formula = serde_json::from_slice(&value)?;
-            formulas.push(formula);
+            formulas.push(serde_json::from_slice(&value)?);
+        }
+        if formulas.is_empty() {
+            formulas.extend(Formula::get_by_id(id, db)?);
         }
         Ok(formulas)
     }
-    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Formula>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("formulas")?;
+
+    pub fn approve(&mut self, approver_id: &str) {
+        self.approved_by = Some(approver_id.to_string());
+        self.approved_at = Some(Utc::now());
+        self.touch();
+    }
+
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<Formula>, Box<dyn std::error::Error>> {
+        index::get(db, id)
+    }
+
+    /// Fórmulas que usam o item informado, na versão atual ou em alguma
+    /// versão guardada.
+    pub fn find_by_item(item_id: &str, db: &sled::Db) -> Result<Vec<Formula>, Box<dyn std::error::Error>> {
+        let mut ids = index::lookup(db, VERSIONS_BY_ITEM, item_id)?;
+        // fórmulas gravadas antes do versionamento só constam no índice atual
+        ids.extend(index::lookup(db, "formulas_by_item", item_id)?);
+        ids.sort();
+        ids.dedup();
         let mut formulas = Vec::new();
-        let start = page * page_size;
-        let name_lower = name.to_lowercase();
-        for result in tree.iter().skip(start).take(page_size) {
+        for id in ids {
+            formulas.extend(Formula::get_by_id(&id, db)?);
+        }
+        Ok(formulas)
+    }
+
+    /// Remove a fórmula com todas as versões guardadas.
+    pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        let mut extra = Vec::new();
+        for result in db.open_tree(VERSIONS)?.scan_prefix(version_prefix(id)) {
+            let (key, value) = result?;
+            let version: Formula = serde_json::from_slice(&value)?;
+            extra.extend(version.itens.iter().map(|it| Extra::Remove(VERSIONS_BY_ITEM, version_item_key(&it.item_id, id))));
+            extra.push(Extra::Remove(VERSIONS, key.to_vec()));
+        }
+        index::delete_with::<Formula>(db, id, &extra)
+    }
+
+    /// Recria o índice por item das versões guardadas.
+    pub(crate) fn rebuild_version_index(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        let index = db.open_tree(VERSIONS_BY_ITEM)?;
+        index.clear()?;
+        for result in db.open_tree(VERSIONS)?.iter() {
             let (_k, value) = result?;
-            let formula: Formula = serde_json::from_slice(&value)?;
-            if formula.nome.to_lowercase().contains(&name_lower) {
-                formulas.push(formula);
+            let version: Formula = serde_json::from_slice(&value)?;
+            for it in &version.itens {
+                index.insert(version_item_key(&it.item_id, &version.id), version.id.as_bytes())?;
             }
         }
-        Ok(formulas)
+        Ok(())
+    }
+
+    pub fn get_all_paginated(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<Formula>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, sort)
+    }
+    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Page<Formula>, Box<dyn std::error::Error>> {
+        search::search(db, name, |r: &Formula| &r.nome, page, page_size)
     }
-    pub fn add_item_by_weight(&mut self, item: Item, peso: f64) {
-        self.itens.push(ItemFormula::new(item, peso));
+    pub fn add_item_by_weight(&mut self, item_id: &str, peso: f64) {
+        self.itens.push(ItemFormula::new(item_id.to_string(), peso));
     }
 
 
-    pub fn add_itens_by_proportion(&mut self, itens: Vec<Item>, proporcoes: Vec<f64>) {
+    pub fn add_itens_by_proportion(&mut self, item_ids: Vec<String>, proporcoes: Vec<f64>) {
         let total_proporcao: f64 = proporcoes.iter().sum();
-        for (item, proporcao) in itens.into_iter().zip(proporcoes.into_iter()) {
+        for (item_id, proporcao) in item_ids.into_iter().zip(proporcoes) {
             let peso = proporcao / total_proporcao;
-            self.itens.push(ItemFormula::new(item, peso));
+            self.itens.push(ItemFormula::new(item_id, peso));
         }
 
       
@@ -119,7 +325,7 @@ impl Formula {
                 0.0
             };
             ItemProporcao {
-                item: item_formula.item.clone(),
+                item_id: item_formula.item_id.clone(),
                 proporcao,
             }
         }).collect()    
@@ -129,7 +335,7 @@ impl Formula {
     pub fn get_proportion(&self, item_id: &str) -> Option<f64> {
         let total_peso: f64 = self.itens.iter().map(|item_formula| item_formula.peso).sum();
         for item_formula in &self.itens {
-            if item_formula.item.id == item_id {
+            if item_formula.item_id == item_id {
                 if total_peso > 0.0 {
                     return Some(item_formula.peso / total_peso);
                 } else {
@@ -144,6 +350,133 @@ impl Formula {
 
     
 
+}
+
+/// Como mudou uma linha da fórmula entre duas versões.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LineDiff {
+    pub item_id: String,
+    pub item_nome: String,
+    pub change: LineChange,
+    pub peso_before: Option<f64>,
+    pub peso_after: Option<f64>,
+    pub tolerance_before: Option<Tolerance>,
+    pub tolerance_after: Option<Tolerance>,
+    pub unit_before: Option<Unit>,
+    pub unit_after: Option<Unit>,
+    pub limits_before: Option<DoseLimits>,
+    pub limits_after: Option<DoseLimits>,
+}
+
+/// Diferenças entre duas versões de uma fórmula; linhas iguais são omitidas.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FormulaDiff {
+    pub formula_id: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub nome_before: String,
+    pub nome_after: String,
+    pub lines: Vec<LineDiff>,
+}
+
+impl FormulaDiff {
+    pub fn between(before: &FormulaView, after: &FormulaView) -> Self {
+        let mut lines = Vec::new();
+        for old in &before.itens {
+            match after.item(&old.item.id) {
+                None => lines.push(LineDiff {
+                    item_id: old.item.id.clone(),
+                    item_nome: old.item.nome.clone(),
+                    change: LineChange::Removed,
+                    peso_before: Some(old.peso),
+                    peso_after: None,
+                    tolerance_before: Some(old.tolerance),
+                    tolerance_after: None,
+                    unit_before: Some(old.unit),
+                    unit_after: None,
+                    limits_before: Some(old.limits),
+                    limits_after: None,
+                }),
+                Some(new) if new.peso != old.peso || new.tolerance != old.tolerance || new.unit != old.unit || new.limits != old.limits => lines.push(LineDiff {
+                    item_id: old.item.id.clone(),
+                    item_nome: new.item.nome.clone(),
+                    change: LineChange::Changed,
+                    peso_before: Some(old.peso),
+                    peso_after: Some(new.peso),
+                    tolerance_before: Some(old.tolerance),
+                    tolerance_after: Some(new.tolerance),
+                    unit_before: Some(old.unit),
+                    unit_after: Some(new.unit),
+                    limits_before: Some(old.limits),
+                    limits_after: Some(new.limits),
+                }),
+                Some(_) => {}
+            }
+        }
+        for new in &after.itens {
+            if before.item(&new.item.id).is_none() {
+                lines.push(LineDiff {
+                    item_id: new.item.id.clone(),
+                    item_nome: new.item.nome.clone(),
+                    change: LineChange::Added,
+                    peso_before: None,
+                    peso_after: Some(new.peso),
+                    tolerance_before: None,
+                    tolerance_after: Some(new.tolerance),
+                    unit_before: None,
+                    unit_after: Some(new.unit),
+                    limits_before: None,
+                    limits_after: Some(new.limits),
+                });
+            }
+        }
+        FormulaDiff {
+            formula_id: after.id.clone(),
+            from_version: before.version,
+            to_version: after.version,
+            nome_before: before.nome.clone(),
+            nome_after: after.nome.clone(),
+            lines,
+        }
+    }
+}
+
+impl Indexed for Formula {
+    const TREE: &'static str = "formulas";
+    const INDEXES: &'static [&'static str] = &["formulas_by_item", "formulas_by_name", "formulas_by_created", "formulas_by_updated"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        let mut entries: Vec<IndexEntry> = self.itens.iter()
+            .map(|itf| IndexEntry::multi("formulas_by_item", &itf.item_id, &self.id))
+            .collect();
+        entries.extend([
+            IndexEntry::by_name("formulas_by_name", &self.nome, &self.id),
+            IndexEntry::by_time("formulas_by_created", &self.created_at, &self.id),
+            IndexEntry::by_time("formulas_by_updated", &self.updated_at, &self.id),
+        ]);
+        entries
+    }
+
+    fn sort_index(field: SortField) -> Option<&'static str> {
+        match field {
+            SortField::Name => Some("formulas_by_name"),
+            SortField::CreatedAt => Some("formulas_by_created"),
+            SortField::UpdatedAt => Some("formulas_by_updated"),
+            SortField::Status => None,
+        }
+    }
 }
 
 impl crate::models::auditable::Auditable for Formula {
@@ -154,4 +487,130 @@ impl crate::models::auditable::Auditable for Formula {
     fn updated_at(&self) -> DateTime<Utc> {
         self.updated_at
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fornecedor::Fornecedor;
+    use crate::models::item::Item;
+    use crate::models::resolver::Resolver;
+
+    #[test]
+    fn test_update_creates_new_version_and_keeps_old() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let fornecedor = Fornecedor::new("X".to_string());
+        fornecedor.save(&db).unwrap();
+        let ids: Vec<String> = ["Farinha", "Ovo", "Leite"].iter().map(|nome| {
+            let item = Item::new(nome.to_string(), fornecedor.id.clone());
+            item.save(&db).unwrap();
+            item.id
+        }).collect();
+
+        let mut formula = Formula::new("Massa".to_string(), vec![]);
+        formula.add_item_by_weight(&ids[0], 10.0);
+        formula.add_item_by_weight(&ids[1], 2.0);
+        formula.approve("sup");
+        formula.save(&db).unwrap();
+
+        formula.itens[0].peso = 12.0;
+        formula.itens.remove(1);
+        formula.add_item_by_weight(&ids[2], 1.0);
+        formula.update(&db).unwrap();
+
+        assert_eq!(formula.version, 2);
+        assert!(formula.approved_by.is_none());
+        let v1 = Formula::get_version(&formula.id, 1, &db).unwrap().unwrap();
+        assert_eq!(v1.itens[0].peso, 10.0);
+        assert_eq!(v1.approved_by.as_deref(), Some("sup"));
+        assert_eq!(Formula::list_versions(&formula.id, &db).unwrap().len(), 2);
+
+        let mut resolver = Resolver::new(&db);
+        let before = resolver.formula(&v1).unwrap();
+        let after = resolver.formula(&formula).unwrap();
+        let diff = FormulaDiff::between(&before, &after);
+        let change = |id: &str| diff.lines.iter().find(|l| l.item_id == id).map(|l| l.change.clone());
+        assert_eq!(diff.lines.len(), 3);
+        assert_eq!(change(&ids[0]), Some(LineChange::Changed));
+        assert_eq!(change(&ids[1]), Some(LineChange::Removed));
+        assert_eq!(change(&ids[2]), Some(LineChange::Added));
+
+        // só a dose máxima mudou: ainda é uma alteração da linha
+        formula.itens[0].limits.max = Some(25.0);
+        formula.update(&db).unwrap();
+        let v3 = resolver.formula(&formula).unwrap();
+        let diff = FormulaDiff::between(&after, &v3);
+        assert_eq!(diff.lines.len(), 1);
+        assert_eq!(diff.lines[0].limits_after.and_then(|l| l.max), Some(25.0));
+
+        // o item saiu da versão atual, mas a v1 ainda o usa
+        let usadas = Formula::find_by_item(&ids[1], &db).unwrap();
+        assert_eq!(usadas.len(), 1);
+        assert_eq!(usadas[0].id, formula.id);
+
+        // excluir a fórmula leva junto as versões e libera o item
+        Formula::delete(&formula.id, &db).unwrap();
+        assert!(Formula::list_versions(&formula.id, &db).unwrap().is_empty());
+        assert!(Formula::find_by_item(&ids[1], &db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tolerance_uses_the_stricter_limit() {
+        let livre = Tolerance::default();
+        assert!(livre.accepts(10.0, 99.0));
+
+        let abs = Tolerance { abs: Some(0.02), pct: None };
+        assert!(abs.accepts(0.5, 0.52));
+        assert!(!abs.accepts(0.5, 0.53));
+
+        // 2% de 10 kg = 0,2 kg, mais restritivo que 0,5 kg
+        let ambos = Tolerance { abs: Some(0.5), pct: Some(2.0) };
+        assert_eq!(ambos.allowed(10.0), Some(0.2));
+        assert!(ambos.accepts(10.0, 9.8));
+        assert!(!ambos.accepts(10.0, 10.3));
+
+        assert!(Tolerance { abs: Some(-1.0), pct: None }.validate().is_err());
+    }
+
+    #[test]
+    fn test_lines_are_stored_in_kg() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let fornecedor = Fornecedor::new("X".to_string());
+        fornecedor.save(&db).unwrap();
+        let mut oleo = Item::new("Óleo".to_string(), fornecedor.id.clone());
+        assert!(oleo.set_unit(Unit::L, None).is_err());
+        oleo.set_unit(Unit::L, Some(0.92)).unwrap();
+        oleo.save(&db).unwrap();
+        let sal = Item::new("Sal".to_string(), fornecedor.id.clone());
+        sal.save(&db).unwrap();
+
+        let input = |item: &Item, peso: f64, unit: Option<Unit>, abs: Option<f64>| FormulaLineInput {
+            item_id: item.id.clone(), peso, unit, tolerance: Tolerance { abs, pct: None }, limits: DoseLimits::default(),
+        };
+        // 500 mL de óleo e 250 g de sal
+        let linha_oleo = input(&oleo, 500.0, Some(Unit::Ml), Some(10.0)).into_line(&oleo).unwrap();
+        assert!((linha_oleo.peso - 0.46).abs() < 1e-12);
+        assert!((linha_oleo.tolerance.abs.unwrap() - 0.0092).abs() < 1e-12);
+        // sem unidade vale a do item
+        assert_eq!(input(&oleo, 1.0, None, None).into_line(&oleo).unwrap().unit, Unit::L);
+        let linha_sal = input(&sal, 250.0, Some(Unit::G), None).into_line(&sal).unwrap();
+        assert!((linha_sal.peso - 0.25).abs() < 1e-12);
+        assert!(input(&sal, 1.0, Some(Unit::L), None).into_line(&sal).is_err());
+        assert!(input(&sal, 0.0, None, None).into_line(&sal).is_err());
+        assert!(input(&sal, f64::NAN, None, None).into_line(&sal).is_err());
+        // limites de dose também são convertidos e conferidos
+        let limites = |min, max| FormulaLineInput { limits: DoseLimits { min, max }, ..input(&sal, 250.0, Some(Unit::G), None) };
+        let linha = limites(Some(50.0), Some(400.0)).into_line(&sal).unwrap();
+        assert_eq!(linha.limits, DoseLimits { min: Some(0.05), max: Some(0.4) });
+        assert!(limites(Some(400.0), Some(50.0)).into_line(&sal).is_err());
+        assert!(limites(Some(-1.0), None).into_line(&sal).is_err());
+
+        let mut formula = Formula::new("F".to_string(), vec![]);
+        formula.itens = vec![linha_oleo, linha_sal];
+        let proporcoes = formula.get_proportions();
+        assert!((proporcoes[0].proporcao - 0.46 / 0.71).abs() < 1e-12);
+
+        let view = Resolver::new(&db).formula(&formula).unwrap();
+        assert!((view.to_kg(&sal.id, 260.0) - 0.26).abs() < 1e-12);
+        assert_eq!(view.itens[0].unit, Unit::Ml);
+    }
+}
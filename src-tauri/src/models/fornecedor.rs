@@ -1,13 +1,22 @@
 use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
 use uuid;
 use crate::models::auditable::Auditable;
+use crate::models::index::{self, Indexed, IndexEntry};
+use crate::models::page::{Page, Sort, SortField};
+use crate::models::search;
 
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Fornecedor {
     pub id: String,
-    pub nome: String
+    pub nome: String,
+    // fornecedores gravados antes destes campos recebem a data da leitura
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
 }
 
 #[allow(dead_code)]
@@ -16,70 +25,70 @@ impl Fornecedor {
 
     pub fn new(nome: String) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
-        Fornecedor { id, nome}
+        let now = Utc::now();
+        Fornecedor { id, nome, created_at: now, updated_at: now }
     }
 
     pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("fornecedores")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-        Ok(())
+        index::save(db, self)
     }
-    pub fn update(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        // Atualiza o fornecedor na árvore de fornecedores
-        let tree = db.open_tree("fornecedores")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-
-        // Atualiza todos os itens que usam este fornecedor
-        let item_tree = db.open_tree("itens")?;
-        for result in item_tree.iter() {
-            let (key, value) = result?;
-            let mut item: crate::models::item::Item = serde_json::from_slice(&value)?;
-            if item.fornecedor.id == self.id {
-                item.fornecedor = self.clone();
-                // atualiza timestamp do item que teve o fornecedor alterado
-                item.touch();
-                let serialized_item = serde_json::to_vec(&item)?;
-                item_tree.insert(key, serialized_item)?;
-            }
-        }
-
-        Ok(())
+    pub fn update(&mut self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        // itens referenciam o fornecedor pelo id, então não precisam ser regravados
+        self.touch();
+        self.save(db)
     }
 
 
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<Fornecedor>, Box<dyn std::error::Error>> {
+        index::get(db, id)
+    }
+
     pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("fornecedores")?;
-        tree.remove(id.as_bytes())?;
-        Ok(())
+        index::delete::<Fornecedor>(db, id)
     }
 
-   pub fn get_all_paginated(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Fornecedor>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("fornecedores")?;
-        let mut fornecedores = Vec::new();
-        let start = page * page_size;
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let fornecedor: Fornecedor = serde_json::from_slice(&value)?;
-            fornecedores.push(fornecedor);
-        }
-        Ok(fornecedores)
+    pub fn get_all_paginated(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<Fornecedor>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, sort)
     }
 
-    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Fornecedor>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("fornecedores")?;
-        let mut fornecedores = Vec::new();
-        let start = page * page_size;
-        let name_lower = name.to_lowercase();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let fornecedor: Fornecedor = serde_json::from_slice(&value)?;
-            if fornecedor.nome.to_lowercase().contains(&name_lower) {
-                fornecedores.push(fornecedor);
-            }
-        }
-        Ok(fornecedores)
+    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Page<Fornecedor>, Box<dyn std::error::Error>> {
+        search::search(db, name, |r: &Fornecedor| &r.nome, page, page_size)
     }
  
-}
\ No newline at end of file
+}
+
+impl Indexed for Fornecedor {
+    const TREE: &'static str = "fornecedores";
+    const INDEXES: &'static [&'static str] = &["fornecedores_by_name", "fornecedores_by_created", "fornecedores_by_updated"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        vec![
+            IndexEntry::by_name("fornecedores_by_name", &self.nome, &self.id),
+            IndexEntry::by_time("fornecedores_by_created", &self.created_at, &self.id),
+            IndexEntry::by_time("fornecedores_by_updated", &self.updated_at, &self.id),
+        ]
+    }
+
+    fn sort_index(field: SortField) -> Option<&'static str> {
+        match field {
+            SortField::Name => Some("fornecedores_by_name"),
+            SortField::CreatedAt => Some("fornecedores_by_created"),
+            SortField::UpdatedAt => Some("fornecedores_by_updated"),
+            SortField::Status => None,
+        }
+    }
+}
+
+impl Auditable for Fornecedor {
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
@@ -0,0 +1,392 @@
+use serde::{de::DeserializeOwned, Serialize};
+use sled::transaction::{ConflictableTransactionError, TransactionError};
+use sled::Transactional;
+use std::ops::Bound;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::models::page::{self, Page, Sort, SortDirection, SortField};
+
+/// Separador entre o valor indexado e o id do registro em índices não únicos.
+const SEP: u8 = 0;
+
+/// Versão do conjunto de índices; incrementar força a reconstrução na inicialização.
+const INDEX_VERSION: u64 = 6;
+
+/// Entrada de um índice secundário: `key → id` na árvore `tree`.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexEntry {
+    pub tree: &'static str,
+    pub key: Vec<u8>,
+    pub unique: bool,
+}
+
+impl IndexEntry {
+    /// Índice em que cada valor aponta para um único registro (ex.: username).
+    pub fn unique(tree: &'static str, value: &str) -> Self {
+        IndexEntry { tree, key: value.as_bytes().to_vec(), unique: true }
+    }
+
+    /// Índice em que um valor aponta para vários registros (ex.: fornecedor → itens).
+    pub fn multi(tree: &'static str, value: &str, id: &str) -> Self {
+        let mut key = value.as_bytes().to_vec();
+        key.push(SEP);
+        key.extend_from_slice(id.as_bytes());
+        IndexEntry { tree, key, unique: false }
+    }
+
+    /// Índice de ordenação por nome, sem acentos nem maiúsculas.
+    pub fn by_name(tree: &'static str, name: &str, id: &str) -> Self {
+        Self::multi(tree, &crate::models::search::normalize(name), id)
+    }
+
+    /// Índice de ordenação cronológica; o RFC 3339 com largura fixa ordena
+    /// lexicograficamente na mesma ordem das datas.
+    pub fn by_time(tree: &'static str, time: &DateTime<Utc>, id: &str) -> Self {
+        Self::multi(tree, &time.to_rfc3339_opts(SecondsFormat::Micros, true), id)
+    }
+}
+
+/// Registro gravado em uma árvore `id → JSON` com índices secundários
+/// atualizados na mesma transação do sled.
+pub(crate) trait Indexed: Serialize + DeserializeOwned {
+    /// Árvore principal.
+    const TREE: &'static str;
+    /// Árvores de índice mantidas por este tipo.
+    const INDEXES: &'static [&'static str];
+
+    fn id(&self) -> &str;
+
+    /// Entradas de índice do registro; cada `tree` deve constar em `INDEXES`.
+    fn index_entries(&self) -> Vec<IndexEntry>;
+
+    /// Árvore de índice que ordena os registros pelo campo, se existir.
+    fn sort_index(_field: SortField) -> Option<&'static str> {
+        None
+    }
+}
+
+fn open_trees<T: Indexed>(db: &sled::Db) -> sled::Result<Vec<sled::Tree>> {
+    let mut trees = vec![db.open_tree(T::TREE)?];
+    for name in T::INDEXES {
+        trees.push(db.open_tree(name)?);
+    }
+    Ok(trees)
+}
+
+fn tree_position<T: Indexed>(name: &str) -> usize {
+    1 + T::INDEXES.iter().position(|n| *n == name)
+        .unwrap_or_else(|| panic!("índice {} não declarado em {}", name, T::TREE))
+}
+
+fn abort<E: std::fmt::Display>(e: E) -> ConflictableTransactionError<String> {
+    ConflictableTransactionError::Abort(e.to_string())
+}
+
+fn flatten(e: TransactionError<String>) -> Box<dyn std::error::Error> {
+    match e {
+        TransactionError::Abort(msg) => msg.into(),
+        TransactionError::Storage(err) => Box::new(err),
+    }
+}
+
+/// Grava o registro e atualiza seus índices atomicamente, removendo as
+/// entradas da versão anterior.
+pub(crate) fn save<T: Indexed>(db: &sled::Db, record: &T) -> Result<(), Box<dyn std::error::Error>> {
+    save_with(db, record, &[])
+}
+
+/// Gravação em outra árvore feita na mesma transação do registro (ex.: o
+/// histórico de versões da fórmula e seu índice).
+pub(crate) enum Extra {
+    Insert(&'static str, Vec<u8>, Vec<u8>),
+    Remove(&'static str, Vec<u8>),
+}
+
+impl Extra {
+    fn tree(&self) -> &'static str {
+        match self {
+            Extra::Insert(tree, _, _) | Extra::Remove(tree, _) => tree,
+        }
+    }
+}
+
+/// Acrescenta às árvores do tipo as árvores usadas por `extra`, uma vez
+/// cada, e devolve a posição de cada gravação extra.
+fn open_extra(db: &sled::Db, trees: &mut Vec<sled::Tree>, extra: &[Extra]) -> sled::Result<Vec<usize>> {
+    let first = trees.len();
+    let mut names: Vec<&str> = Vec::new();
+    let mut positions = Vec::new();
+    for write in extra {
+        let i = match names.iter().position(|n| *n == write.tree()) {
+            Some(i) => i,
+            None => {
+                names.push(write.tree());
+                trees.push(db.open_tree(write.tree())?);
+                names.len() - 1
+            }
+        };
+        positions.push(first + i);
+    }
+    Ok(positions)
+}
+
+fn apply_extra(tx: &[sled::transaction::TransactionalTree], extra: &[Extra], positions: &[usize]) -> sled::transaction::ConflictableTransactionResult<(), String> {
+    for (write, &pos) in extra.iter().zip(positions) {
+        match write {
+            Extra::Insert(_, key, value) => { tx[pos].insert(key.clone(), value.clone())?; }
+            Extra::Remove(_, key) => { tx[pos].remove(key.clone())?; }
+        }
+    }
+    Ok(())
+}
+
+/// Como `save`, com gravações extras na mesma transação.
+pub(crate) fn save_with<T: Indexed>(db: &sled::Db, record: &T, extra: &[Extra]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut trees = open_trees::<T>(db)?;
+    let positions = open_extra(db, &mut trees, extra)?;
+    let serialized = serde_json::to_vec(record)?;
+    let id = record.id().as_bytes();
+    let entries = record.index_entries();
+
+    trees.as_slice().transaction(|tx| {
+        if let Some(old_bytes) = tx[0].get(id)? {
+            let old: T = serde_json::from_slice(&old_bytes).map_err(abort)?;
+            for entry in old.index_entries() {
+                tx[tree_position::<T>(entry.tree)].remove(entry.key)?;
+            }
+        }
+        for entry in &entries {
+            let tree = &tx[tree_position::<T>(entry.tree)];
+            if entry.unique {
+                if let Some(owner) = tree.get(&entry.key)? {
+                    if owner.as_ref() != id {
+                        return Err(abort(format!("Valor duplicado em {}: {}", entry.tree, String::from_utf8_lossy(&entry.key))));
+                    }
+                }
+            }
+            tree.insert(entry.key.clone(), id)?;
+        }
+        apply_extra(tx, extra, &positions)?;
+        tx[0].insert(id, serialized.clone())?;
+        Ok(())
+    }).map_err(flatten)
+}
+
+/// Remove o registro e suas entradas de índice atomicamente.
+pub(crate) fn delete<T: Indexed>(db: &sled::Db, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    delete_with::<T>(db, id, &[])
+}
+
+/// Como `delete`, com gravações extras na mesma transação.
+pub(crate) fn delete_with<T: Indexed>(db: &sled::Db, id: &str, extra: &[Extra]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut trees = open_trees::<T>(db)?;
+    let positions = open_extra(db, &mut trees, extra)?;
+    trees.as_slice().transaction(|tx| {
+        if let Some(old_bytes) = tx[0].remove(id.as_bytes())? {
+            let old: T = serde_json::from_slice(&old_bytes).map_err(abort)?;
+            for entry in old.index_entries() {
+                tx[tree_position::<T>(entry.tree)].remove(entry.key)?;
+            }
+        }
+        apply_extra(tx, extra, &positions)?;
+        Ok(())
+    }).map_err(flatten)
+}
+
+pub(crate) fn get<T: Indexed>(db: &sled::Db, id: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let tree = db.open_tree(T::TREE)?;
+    match tree.get(id.as_bytes())? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+type KvIter = Box<dyn Iterator<Item = sled::Result<(sled::IVec, sled::IVec)>>>;
+
+/// Lista os registros em ordem de chave ou, com `sort`, na ordem da árvore
+/// de índice correspondente.
+///
+/// Com `cursor` a leitura começa logo após a chave informada (range scan);
+/// sem ele, as `page * page_size` primeiras chaves são puladas.
+pub(crate) fn list<T: Indexed>(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<T>, Box<dyn std::error::Error>> {
+    let tree = db.open_tree(T::TREE)?;
+    let (scan, descending) = match sort {
+        Some(sort) => {
+            let name = T::sort_index(sort.field)
+                .ok_or_else(|| format!("Ordenação por {:?} não disponível para {}", sort.field, T::TREE))?;
+            (db.open_tree(name)?, sort.direction == SortDirection::Desc)
+        }
+        None => (tree.clone(), false),
+    };
+    let by_index = sort.is_some();
+
+    let after = cursor.map(page::decode_cursor).transpose()?;
+    let mut iter: KvIter = match (after, descending) {
+        (Some(key), false) => Box::new(scan.range::<Vec<u8>, _>((Bound::Excluded(key), Bound::Unbounded))),
+        (Some(key), true) => Box::new(scan.range::<Vec<u8>, _>((Bound::Unbounded, Bound::Excluded(key))).rev()),
+        (None, false) => Box::new(scan.iter().skip(page * page_size)),
+        (None, true) => Box::new(scan.iter().rev().skip(page * page_size)),
+    };
+
+    let mut items = Vec::new();
+    let mut last_key = None;
+    for result in iter.by_ref().take(page_size) {
+        let (key, value) = result?;
+        if by_index {
+            let id = String::from_utf8_lossy(&value).into_owned();
+            if let Some(record) = get::<T>(db, &id)? {
+                items.push(record);
+            }
+        } else {
+            items.push(serde_json::from_slice(&value)?);
+        }
+        last_key = Some(key);
+    }
+    let has_more = iter.next().transpose()?.is_some();
+
+    Ok(Page {
+        items,
+        total: tree.len(),
+        page,
+        page_size,
+        cursor: if has_more { last_key.map(|k| page::encode_cursor(&k)) } else { None },
+    })
+}
+
+/// Id do registro com o valor informado em um índice único.
+pub(crate) fn lookup_unique(db: &sled::Db, index: &str, value: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let tree = db.open_tree(index)?;
+    Ok(tree.get(value.as_bytes())?.map(|id| String::from_utf8_lossy(&id).into_owned()))
+}
+
+/// Ids dos registros com o valor informado em um índice não único.
+pub(crate) fn lookup(db: &sled::Db, index: &str, value: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let tree = db.open_tree(index)?;
+    let mut prefix = value.as_bytes().to_vec();
+    prefix.push(SEP);
+    let mut ids = Vec::new();
+    for result in tree.scan_prefix(prefix) {
+        let (_k, id) = result?;
+        ids.push(String::from_utf8_lossy(&id).into_owned());
+    }
+    Ok(ids)
+}
+
+/// Carrega os registros apontados por um índice não único.
+pub(crate) fn load_by<T: Indexed>(db: &sled::Db, index: &str, value: &str) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+    let mut records = Vec::new();
+    for id in lookup(db, index, value)? {
+        if let Some(record) = get::<T>(db, &id)? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// Recria do zero os índices de um tipo a partir da árvore principal.
+pub(crate) fn rebuild<T: Indexed>(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+    for name in T::INDEXES {
+        db.open_tree(name)?.clear()?;
+    }
+    let trees = open_trees::<T>(db)?;
+    for result in trees[0].iter() {
+        let (id, value) = result?;
+        let record: T = serde_json::from_slice(&value)?;
+        for entry in record.index_entries() {
+            trees[tree_position::<T>(entry.tree)].insert(entry.key, id.clone())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reconstrói todos os índices quando o banco foi gravado por uma versão
+/// anterior do esquema de índices.
+pub(crate) fn ensure_indexes(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::models::{fornecedor::Fornecedor, item::Item, formula::Formula, processo::Processo, sprint::Sprint, user::User};
+
+    let meta = db.open_tree("meta")?;
+    let current = meta.get("index_version")?
+        .map(|v| u64::from_be_bytes(v.as_ref().try_into().unwrap_or([0; 8])))
+        .unwrap_or(0);
+    if current >= INDEX_VERSION {
+        return Ok(());
+    }
+
+    rebuild::<Fornecedor>(db)?;
+    rebuild::<Item>(db)?;
+    rebuild::<Formula>(db)?;
+    Formula::rebuild_version_index(db)?;
+    rebuild::<Processo>(db)?;
+    rebuild::<Sprint>(db)?;
+    rebuild::<User>(db)?;
+    meta.insert("index_version", &INDEX_VERSION.to_be_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fornecedor::Fornecedor;
+    use crate::models::item::Item;
+    use crate::models::user::{Role, User};
+
+    fn temp_db() -> sled::Db {
+        sled::Config::default().temporary(true).open().unwrap()
+    }
+
+    #[test]
+    fn test_index_follows_update_and_delete() {
+        let db = temp_db();
+        let f1 = Fornecedor::new("F1".to_string());
+        let f2 = Fornecedor::new("F2".to_string());
+        f1.save(&db).unwrap();
+        f2.save(&db).unwrap();
+        let mut item = Item::new("Açúcar".to_string(), f1.id.clone());
+        item.save(&db).unwrap();
+        assert_eq!(Item::find_by_fornecedor(&f1.id, &db).unwrap().len(), 1);
+
+        item.fornecedor_id = f2.id.clone();
+        item.save(&db).unwrap();
+        assert!(Item::find_by_fornecedor(&f1.id, &db).unwrap().is_empty());
+        assert_eq!(Item::find_by_fornecedor(&f2.id, &db).unwrap()[0].id, item.id);
+
+        Item::delete(&item.id, &db).unwrap();
+        assert!(Item::find_by_fornecedor(&f2.id, &db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unique_index_rejects_duplicate_username() {
+        let db = temp_db();
+        User::new("op".to_string(), "segredo".to_string(), Role::User).unwrap().save(&db).unwrap();
+        let other = User::new("op".to_string(), "segredo".to_string(), Role::User).unwrap();
+        assert!(other.save(&db).is_err());
+        assert!(User::get_by_id(&other.id, &db).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_role_index_counts_admins() {
+        let db = temp_db();
+        let mut user = User::new("chefe".to_string(), "segredo".to_string(), Role::from_name("Admin").unwrap()).unwrap();
+        user.save(&db).unwrap();
+        assert_eq!(User::count_admins(&db).unwrap(), 1);
+
+        user.role = Role::from_name("User").unwrap();
+        user.save(&db).unwrap();
+        assert_eq!(User::count_admins(&db).unwrap(), 0);
+        assert!(Role::from_name("root").is_err());
+    }
+
+    #[test]
+    fn test_rebuild_indexes_existing_records() {
+        let db = temp_db();
+        let user = User::new("antigo".to_string(), "segredo".to_string(), Role::User).unwrap();
+        // simula um banco gravado antes dos índices existirem
+        db.open_tree("users").unwrap()
+            .insert(user.id.as_bytes(), serde_json::to_vec(&user).unwrap()).unwrap();
+        assert!(User::find_by_username("antigo", &db).unwrap().is_none());
+
+        ensure_indexes(&db).unwrap();
+        assert_eq!(User::find_by_username("antigo", &db).unwrap().unwrap().id, user.id);
+    }
+}
@@ -1,14 +1,26 @@
 use serde::{Serialize, Deserialize};
 use uuid;
-use crate::models::fornecedor::Fornecedor;
 use crate::models::auditable::Auditable;
+use crate::models::index::{self, Indexed, IndexEntry};
+use crate::models::page::{Page, Sort, SortField};
+use crate::models::search;
 use  chrono::{DateTime, Utc};
+use crate::models::unit::Unit;
+use crate::models::quantity::Mass;
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Item {
     pub id: String,
     pub nome: String,
-    pub fornecedor: Fornecedor,
     pub fornecedor_id: String,
+    /// Unidade usada por padrão nas linhas de fórmula deste item.
+    #[serde(default)]
+    pub unit: Unit,
+    /// kg por litro para líquidos; kg por unidade para itens contados.
+    #[serde(default)]
+    pub density: Option<f64>,
+    /// Divisão da balança em que o item é pesado.
+    #[serde(default)]
+    pub resolution: Option<Mass>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
@@ -17,91 +29,82 @@ pub struct Item {
 
 
 
+/// Unidade, densidade e resolução informadas no cadastro do item.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ItemMeasure {
+    pub unit: Unit,
+    pub density: Option<f64>,
+    pub resolution: Option<Mass>,
+}
+
 #[allow(dead_code)]
 impl Item {
-    pub fn new(nome: String, fornecedor: Fornecedor) -> Self {
+    pub fn new(nome: String, fornecedor_id: String) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
-        let fornecedor_id = fornecedor.id.clone();
         let now = Utc::now();
-        Item { id, nome, fornecedor, fornecedor_id, created_at: now, updated_at: now }
+        Item { id, nome, fornecedor_id, unit: Unit::Kg, density: None, resolution: None, created_at: now, updated_at: now }
     }
 
-    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("itens")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
+    /// Troca a unidade padrão; a densidade precisa permitir converter para kg.
+    pub fn set_unit(&mut self, unit: Unit, density: Option<f64>) -> Result<(), String> {
+        if density.is_some_and(|d| !d.is_finite() || d <= 0.0) {
+            return Err("Densidade inválida".to_string());
+        }
+        unit.kg_factor(density)?;
+        self.unit = unit;
+        self.density = density;
+        Ok(())
+    }
+
+    pub fn set_measure(&mut self, measure: ItemMeasure) -> Result<(), String> {
+        if measure.resolution.is_some_and(|r| r <= Mass::ZERO) {
+            return Err("Resolução inválida".to_string());
+        }
+        self.set_unit(measure.unit, measure.density)?;
+        self.resolution = measure.resolution;
         Ok(())
     }
+
+    /// Quantos kg vale uma unidade `unit` deste item.
+    pub fn kg_factor(&self, unit: Unit) -> Result<f64, String> {
+        unit.kg_factor(self.density)
+    }
+
+    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        index::save(db, self)
+    }
     pub fn update(&mut self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        // atualiza timestamp do próprio item e persiste
+        // fórmulas guardam só o id do item, então não precisam ser regravadas
         self.touch();
-        let tree = db.open_tree("itens")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-
-        // Atualiza todas as fórmulas que usam este item, preservando o peso
-        let formula_tree = db.open_tree("formulas")?;
-        for result in formula_tree.iter() {
-            let (key, value) = result?;
-            let mut formula: crate::models::formula::Formula = serde_json::from_slice(&value)?;
-            let mut updated = false;
-            for item_formula in &mut formula.itens {
-                if item_formula.item.id == self.id {
-                    item_formula.item = self.clone();
-                    updated = true;
-                }
-            }
-            if updated {
-                // marca atualização na fórmula antes de persistir
-                formula.touch();
-                let serialized_formula = serde_json::to_vec(&formula)?;
-                formula_tree.insert(key, serialized_formula)?;
-            }
-        }
+        self.save(db)
+    }
 
-        Ok(())
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<Item>, Box<dyn std::error::Error>> {
+        index::get(db, id)
+    }
+
+    /// Todos os itens de um fornecedor (sem paginação).
+    pub fn find_by_fornecedor(fornecedor_id: &str, db: &sled::Db) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
+        index::load_by(db, "itens_by_fornecedor", fornecedor_id)
     }
 
     pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("itens")?;
-        tree.remove(id.as_bytes())?;
-        Ok(())
+        index::delete::<Item>(db, id)
     }
 
-    pub fn get_all(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("itens")?;
-        let mut itens = Vec::new();
-        let start = page * page_size;
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let item: Item = serde_json::from_slice(&value)?;
-            itens.push(item);
-        }
-        Ok(itens)
+    pub fn get_all(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<Item>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, sort)
     }
-    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("itens")?;
-        let mut itens = Vec::new();
-        let start = page * page_size;
-        let name_lower = name.to_lowercase();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let item: Item = serde_json::from_slice(&value)?;
-            if item.nome.to_lowercase().contains(&name_lower) {
-                itens.push(item);
-            }
-        }
-        Ok(itens)
+    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Page<Item>, Box<dyn std::error::Error>> {
+        search::search(db, name, |r: &Item| &r.nome, page, page_size)
     }
     
-        pub fn list_by_fornecedor(fornecedor_id: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("itens")?;
+    pub fn list_by_fornecedor(fornecedor_id: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Item>, Box<dyn std::error::Error>> {
+        let ids = index::lookup(db, "itens_by_fornecedor", fornecedor_id)?;
         let mut itens = Vec::new();
-        let start = page * page_size;
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let item: Item = serde_json::from_slice(&value)?;
-            if item.fornecedor_id == fornecedor_id {
+        for id in ids.iter().skip(page * page_size).take(page_size) {
+            if let Some(item) = Item::get_by_id(id, db)? {
                 itens.push(item);
             }
         }
@@ -110,6 +113,33 @@ impl Item {
 }
 
 
+impl Indexed for Item {
+    const TREE: &'static str = "itens";
+    const INDEXES: &'static [&'static str] = &["itens_by_fornecedor", "itens_by_name", "itens_by_created", "itens_by_updated"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        vec![
+            IndexEntry::multi("itens_by_fornecedor", &self.fornecedor_id, &self.id),
+            IndexEntry::by_name("itens_by_name", &self.nome, &self.id),
+            IndexEntry::by_time("itens_by_created", &self.created_at, &self.id),
+            IndexEntry::by_time("itens_by_updated", &self.updated_at, &self.id),
+        ]
+    }
+
+    fn sort_index(field: SortField) -> Option<&'static str> {
+        match field {
+            SortField::Name => Some("itens_by_name"),
+            SortField::CreatedAt => Some("itens_by_created"),
+            SortField::UpdatedAt => Some("itens_by_updated"),
+            SortField::Status => None,
+        }
+    }
+}
+
 impl Auditable for Item {
     fn touch(&mut self) {
         
@@ -5,6 +5,15 @@ pub mod sprint;
 pub mod processo;
 pub mod user;
 pub mod auditable;
+pub mod audit;
+pub mod index;
+pub mod page;
+pub mod search;
+pub mod resolver;
+pub mod compensation;
+pub mod reconciliation;
+pub mod unit;
+pub mod quantity;
 
 use std::sync::OnceLock;
 
@@ -38,21 +47,14 @@ pub(crate) fn connect_db() -> &'static sled::Db {
 
 pub(crate) fn create_adm_if_not_exists(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
     use crate::models::user::User;
-    let tree = db.open_tree("users")?;
-    let admin_exists = tree.iter().any(|result| {
-        if let Ok((_key, value)) = result {
-            if let Ok(user) = serde_json::from_slice::<User>(&value) {
-                return user.username == "admin";
-            }
-        }
-        false
-    });
 
-    if !admin_exists {
-        let admin_user = User::new("admin".to_string(), "admin".to_string(), crate::models::user::Role::Admin);
+    if User::find_by_username("admin", db)?.is_none() {
+        let mut admin_user = User::new("admin".to_string(), "admin".to_string(), crate::models::user::Role::Admin)?;
+        // senha padrão conhecida: troca obrigatória no primeiro login
+        admin_user.must_change_password = true;
         admin_user.save(db)?;
-        println!("Admin user created with username 'admin' and password 'admin'");
+        println!("Admin user created with username 'admin' and password 'admin' (password change required on first login)");
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,19 +1,186 @@
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use crate::models::sprint::Sprint;
-use crate::models::formula::Formula;
+use crate::models::sprint::{Deferral, Sprint, Suggestion};
+use crate::models::quantity::Mass;
+use crate::models::resolver::FormulaView;
 use serde::{Serialize, Deserialize};
 use uuid;
 use crate::models::auditable::Auditable;
+use crate::models::index::{self, Indexed, IndexEntry};
+use crate::models::page::{Page, Sort, SortField};
+use crate::models::search;
+use crate::models::user::User;
+use crate::models::compensation::CompensationStrategy;
+use crate::models::reconciliation::Reconciliation;
 
+/// Sprints restantes considerados sem plano: a correção vai inteira no próximo.
+const DEFAULT_REMAINING_SPRINTS: usize = 1;
+
+/// Situação do processo. Os valores gravados como texto pelas versões
+/// anteriores são lidos pelos aliases.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessoStatus {
+    /// Criado, nenhum sprint iniciado ainda.
+    Planned,
+    #[serde(alias = "Em Andamento")]
+    InProgress,
+    Paused,
+    #[serde(alias = "Terminado")]
+    Finished,
+    Cancelled,
+}
+
+impl ProcessoStatus {
+    /// Tabela de transições permitidas.
+    pub fn can_transition(self, to: ProcessoStatus) -> bool {
+        use ProcessoStatus::*;
+        matches!(
+            (self, to),
+            (Planned, InProgress)
+                | (Planned, Cancelled)
+                | (InProgress, Paused)
+                | (InProgress, Finished)
+                | (InProgress, Cancelled)
+                | (Paused, InProgress)
+                | (Paused, Cancelled)
+        )
+    }
+
+    /// Aceita novos sprints.
+    pub fn is_active(self) -> bool {
+        matches!(self, ProcessoStatus::Planned | ProcessoStatus::InProgress)
+    }
+
+    /// Finalizado ou cancelado; não sai mais dessa situação.
+    pub fn is_closed(self) -> bool {
+        matches!(self, ProcessoStatus::Finished | ProcessoStatus::Cancelled)
+    }
+
+    /// Transições que exigem um motivo.
+    fn requires_reason(self) -> bool {
+        matches!(self, ProcessoStatus::Paused | ProcessoStatus::Cancelled)
+    }
+
+    /// Chave usada no índice por situação.
+    pub fn key(self) -> &'static str {
+        match self {
+            ProcessoStatus::Planned => "Planned",
+            ProcessoStatus::InProgress => "InProgress",
+            ProcessoStatus::Paused => "Paused",
+            ProcessoStatus::Finished => "Finished",
+            ProcessoStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+/// Registro de uma mudança de situação do processo.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProcessoStatusChange {
+    pub from: ProcessoStatus,
+    pub to: ProcessoStatus,
+    pub reason: Option<String>,
+    pub user_id: String,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Planejamento do lote: massa total, número de sprints e a escala aplicada
+/// aos pesos da fórmula em cada sprint.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ProcessoPlan {
+    pub target_mass: f64,
+    pub planned_sprints: usize,
+    pub scale: f64,
+}
+
+impl ProcessoPlan {
+    /// Deriva o plano a partir da massa total e/ou do número de sprints.
+    /// Só com a massa, usa o menor número de sprints que cabe no misturador
+    /// (ou no tamanho da fórmula, sem capacidade informada); só com o número
+    /// de sprints, cada sprint tem a massa da fórmula.
+    pub fn derive(formula_mass: f64, target_mass: Option<f64>, planned_sprints: Option<usize>, capacity: Option<f64>) -> Result<Option<ProcessoPlan>, String> {
+        if formula_mass <= 0.0 {
+            return match (target_mass, planned_sprints) {
+                (None, None) => Ok(None),
+                _ => Err("Fórmula sem peso não pode ser planejada".to_string()),
+            };
+        }
+        if let Some(mass) = target_mass {
+            if !mass.is_finite() || mass <= 0.0 {
+                return Err("Massa total inválida".to_string());
+            }
+        }
+        if planned_sprints == Some(0) {
+            return Err("Número de sprints inválido".to_string());
+        }
+        let (target_mass, planned_sprints) = match (target_mass, planned_sprints) {
+            (None, None) => return Ok(None),
+            (None, Some(count)) => (formula_mass * count as f64, count),
+            (Some(mass), Some(count)) => (mass, count),
+            (Some(mass), None) => {
+                let por_sprint = capacity.unwrap_or(formula_mass);
+                (mass, ((mass / por_sprint) - 1e-9).ceil().max(1.0) as usize)
+            }
+        };
+        let scale = target_mass / (formula_mass * planned_sprints as f64);
+        if let Some(cap) = capacity {
+            if formula_mass * scale > cap + 1e-9 {
+                return Err(format!("Cada sprint teria {:.2} kg, acima da capacidade do misturador ({:.2} kg)", formula_mass * scale, cap));
+            }
+        }
+        Ok(Some(ProcessoPlan { target_mass, planned_sprints, scale }))
+    }
+}
+
+/// Opções informadas na criação do processo.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProcessoSettings {
+    pub compensation: CompensationStrategy,
+    pub mixer_capacity: Option<f64>,
+    pub target_mass: Option<f64>,
+    pub planned_sprints: Option<usize>,
+    /// Divisão da balança usada no processo.
+    pub resolution: Option<Mass>,
+}
+
+/// Andamento do processo frente ao planejado.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessoProgress {
+    pub sprints_done: usize,
+    pub planned_sprints: Option<usize>,
+    pub mass_produced: f64,
+    pub target_mass: Option<f64>,
+    /// Todos os sprints planejados foram concluídos.
+    pub complete: bool,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Processo {
     pub id: String,
     pub nome: String,
-    pub formula: Formula,
-    pub status: String,
+    /// Cópia da fórmula, com os itens resolvidos, feita na criação do processo;
+    /// edições posteriores da fórmula ou dos itens não alteram o histórico.
+    pub formula: FormulaView,
+    pub status: ProcessoStatus,
+    #[serde(default)]
+    pub status_history: Vec<ProcessoStatusChange>,
+    /// Massa total planejada; sem plano, a massa de um sprint da fórmula.
     pub weight: f64,
+    #[serde(default)]
+    pub plan: Option<ProcessoPlan>,
+    /// Como o erro acumulado é compensado nos sprints seguintes.
+    #[serde(default)]
+    pub compensation: CompensationStrategy,
+    /// Capacidade do misturador, em kg por sprint.
+    #[serde(default)]
+    pub mixer_capacity: Option<f64>,
+    /// Divisão da balança do processo; vale para os itens sem resolução própria.
+    #[serde(default)]
+    pub resolution: Option<Mass>,
+    /// Fechamento gerado ao finalizar o processo.
+    #[serde(default)]
+    pub reconciliation: Option<Reconciliation>,
     pub sprints: Vec<Sprint>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -21,10 +188,14 @@ pub struct Processo {
 
 #[allow(dead_code)]
 impl Processo {
-    pub fn new(nome: String, formula: Formula, status: String, weight: f64) -> Self {
+    pub fn new(nome: String, formula: FormulaView, weight: f64) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
         let now = Utc::now();
-        Processo { id, nome, formula, status, weight, sprints: Vec::new(), created_at: now, updated_at: now }
+        Processo {
+            id, nome, formula, status: ProcessoStatus::Planned, status_history: Vec::new(),
+            weight, plan: None, compensation: CompensationStrategy::default(), mixer_capacity: None, resolution: None, reconciliation: None, sprints: Vec::new(),
+            created_at: now, updated_at: now,
+        }
     }
 
     pub fn add_sprint(&mut self, mut sprint: Sprint) {
@@ -33,114 +204,292 @@ impl Processo {
         self.touch();
     }
 
-    pub fn accumulate_divergences(&self) -> HashMap<String, f64> {
-        let mut acc: HashMap<String, f64> = HashMap::new();
-        for sprint in &self.sprints {
+    /// Abandona um sprint em aberto e o guarda no histórico como abortado.
+    /// Processos encerrados não recebem mais sprints, nem abortados.
+    pub fn abort_sprint(&mut self, mut sprint: Sprint, user_id: &str, reason: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.status.is_closed() {
+            return Err(format!("Processo {:?} não pode ser alterado", self.status).into());
+        }
+        sprint.abort(user_id, reason)?;
+        self.add_sprint(sprint);
+        Ok(())
+    }
+
+    /// Aplica o planejamento do lote; a massa total passa a ser a planejada.
+    /// A dose planejada de cada linha, já na escala do plano, precisa caber
+    /// nos limites da linha.
+    pub fn set_plan(&mut self, target_mass: Option<f64>, planned_sprints: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
+        let plan = ProcessoPlan::derive(self.formula.total_weight(), target_mass, planned_sprints, self.mixer_capacity)?;
+        let scale = plan.map(|p| p.scale).unwrap_or(1.0);
+        for fi in &self.formula.itens {
+            let dose = fi.peso * scale;
+            if fi.limits.min.is_some_and(|min| dose < min) || fi.limits.max.is_some_and(|max| dose > max) {
+                return Err(format!("Dose de {} por sprint ({:.3} kg) fora dos limites da linha", fi.item.nome, dose).into());
+            }
+        }
+        self.plan = plan;
+        if let Some(plan) = self.plan {
+            self.weight = plan.target_mass;
+        }
+        self.touch();
+        Ok(())
+    }
+
+    /// Escala dos pesos da fórmula em cada sprint.
+    pub fn scale(&self) -> f64 {
+        self.plan.map(|p| p.scale).unwrap_or(1.0)
+    }
+
+    /// Peso planejado do item em um sprint.
+    pub fn base_weight(&self, item_id: &str) -> Mass {
+        self.formula.item(item_id).map(|fi| Mass::from_kg(fi.peso * self.scale())).unwrap_or_default()
+    }
+
+    /// Resolução em que o item é pesado: a do item ou, sem ela, a do processo.
+    pub fn resolution(&self, item_id: &str) -> Mass {
+        self.formula.item(item_id)
+            .and_then(|fi| fi.item.resolution)
+            .or(self.resolution)
+            .unwrap_or_default()
+    }
+
+    /// Dose mínima (zero, sem limite) e máxima por sprint da linha do item.
+    pub fn dose_limits(&self, item_id: &str) -> (Mass, Option<Mass>) {
+        let limits = self.formula.item(item_id).map(|fi| fi.limits).unwrap_or_default();
+        (limits.min.map(Mass::from_kg).unwrap_or_default(), limits.max.map(Mass::from_kg))
+    }
+
+    /// Massa planejada de um sprint.
+    pub fn sprint_mass(&self) -> f64 {
+        self.formula.total_weight() * self.scale()
+    }
+
+    /// Sprints concluídos e não anulados.
+    pub fn completed_sprints(&self) -> usize {
+        self.sprints.iter().filter(|s| s.counts()).count()
+    }
+
+    /// Sprints que faltam pelo plano, incluindo o próximo.
+    pub fn remaining_sprints(&self) -> Option<usize> {
+        self.plan.map(|p| p.planned_sprints.saturating_sub(self.completed_sprints()))
+    }
+
+    pub fn progress(&self) -> ProcessoProgress {
+        let mass_produced = self.sprints.iter()
+            .filter(|s| s.counts())
+            .flat_map(|s| s.itens.iter().filter_map(|it| it.actual))
+            .sum::<Mass>()
+            .kg();
+        let sprints_done = self.completed_sprints();
+        ProcessoProgress {
+            sprints_done,
+            planned_sprints: self.plan.map(|p| p.planned_sprints),
+            mass_produced,
+            target_mass: self.plan.map(|p| p.target_mass),
+            complete: self.plan.is_some_and(|p| sprints_done >= p.planned_sprints),
+        }
+    }
+
+    /// Define a capacidade do misturador, que precisa comportar ao menos um
+    /// sprint do processo.
+    pub fn set_mixer_capacity(&mut self, capacity: Option<f64>) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(cap) = capacity {
+            if !cap.is_finite() || cap <= 0.0 {
+                return Err("Capacidade do misturador inválida".into());
+            }
+            if cap < self.sprint_mass() {
+                return Err(format!("Capacidade do misturador ({:.2} kg) menor que a massa do sprint ({:.2} kg)", cap, self.sprint_mass()).into());
+            }
+        }
+        self.mixer_capacity = capacity;
+        self.touch();
+        Ok(())
+    }
+
+    /// Reajusta o sprint em aberto para manter a proporção, se esse for o
+    /// modo de compensação do processo.
+    pub fn rebalance(&self, sprint: &mut Sprint) {
+        if self.compensation == CompensationStrategy::RatioPreserving {
+            sprint.apply_ratio(&self.formula.get_proportions(), self.sprint_mass(), self.mixer_capacity);
+        }
+    }
+
+    pub fn sprint_mut(&mut self, sprint_id: &str) -> Option<&mut Sprint> {
+        self.sprints.iter_mut().find(|s| s.id == sprint_id)
+    }
+
+    /// Erro acumulado por item. Sprints abortados ou anulados não entram na conta.
+    pub fn accumulate_divergences(&self) -> HashMap<String, Mass> {
+        let mut acc: HashMap<String, Mass> = HashMap::new();
+        for sprint in self.sprints.iter().filter(|s| s.counts()) {
             for item in &sprint.itens {
-                let id = item.item.id.clone();
-                // Encontra o peso base na fórmula
-                let base_weight = self.formula.itens.iter()
-                    .find(|fi| fi.item.id == id)
-                    .map(|fi| fi.peso)
-                    .unwrap_or(0.0);
+                let id = item.item_id.clone();
+                // Peso base do item no sprint (fórmula na escala do plano)
+                let base_weight = self.base_weight(&id);
                 // Erro = actual - base_weight
                 let error = match item.actual {
-                    Some(actual) => {
-                        let err = actual - base_weight;
-                        println!("📊 Sprint {}, Item {}: actual={:.2}, base={:.2}, erro={:.2}", 
-                                 sprint.numero, item.item.nome, actual, base_weight, err);
-                        err
-                    },
-                    None => 0.0,
+                    Some(actual) => actual - base_weight,
+                    // item não dosado: falta todo o peso base
+                    None if item.skipped => -base_weight,
+                    None => Mass::ZERO,
                 };
-                let entry = acc.entry(id).or_insert(0.0);
-                *entry += error;
+                *acc.entry(id).or_default() += error;
             }
         }
-        println!("📊 Erro acumulado total: {:?}", acc);
         acc
     }
 
-    pub fn suggest_next_sprint_targets(&self, _remaining_sprints: usize) -> HashMap<String, f64> {
-        let mut suggestions: HashMap<String, f64> = HashMap::new();
-        
-        println!("\n🎯 CALCULANDO SUGESTÕES PARA PRÓXIMO SPRINT");
-        println!("📝 Total de sprints executados: {}", self.sprints.len());
+    /// Resíduo de arredondamento do último sprint que conta. Os anteriores já
+    /// foram devolvidos nos sprints seguintes e estão no erro acumulado.
+    fn last_rounding(&self) -> HashMap<String, Mass> {
+        self.sprints.iter().rev()
+            .find(|s| s.counts())
+            .map(|s| s.itens.iter().map(|it| (it.item_id.clone(), it.rounding)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn suggest_next_sprint_targets(&self) -> HashMap<String, Suggestion> {
+        let mut suggestions: HashMap<String, Suggestion> = HashMap::new();
+
+        // os sprints restantes vêm do plano; sem plano, o padrão do backend
+        let remaining_sprints = self.remaining_sprints().unwrap_or(DEFAULT_REMAINING_SPRINTS).max(1);
         
         // Acumula divergências (erro acumulado) por item
         let accumulated_errors = self.accumulate_divergences();
+        let roundings = self.last_rounding();
         
         for item_formula in &self.formula.itens {
             let id = item_formula.item.id.clone();
-            let nome = &item_formula.item.nome;
             // Peso base por sprint da fórmula
-            let base_weight = item_formula.peso;
+            let base_weight = self.base_weight(&id);
             
             // Erro acumulado deste item em todos os sprints anteriores
-            let accumulated_error = accumulated_errors.get(&id).cloned().unwrap_or(0.0);
+            let accumulated_error = accumulated_errors.get(&id).copied().unwrap_or_default();
+            // O arredondamento do último sprint não é erro de dosagem: volta
+            // por inteiro no próximo, qualquer que seja a estratégia
+            let rounding = roundings.get(&id).copied().unwrap_or_default();
             
-            // Sugestão = peso_base - erro_acumulado
-            // Se acumulou +1.5kg de excesso, próximo sprint sugere -1.5kg
-            let mut suggested_next = base_weight - accumulated_error;
-
-            println!("🎯 Item {}: base={:.2}kg, erro_acumulado={:.2}kg, sugestão={:.2}kg", 
-                     nome, base_weight, accumulated_error, suggested_next);
-
-            // Protege contra NaN / infinito
-            if !suggested_next.is_finite() {
-                suggested_next = 0.0;
+            // Sugestão = peso_base - correção; com a compensação integral a
+            // correção é todo o erro (+1.5kg de excesso → próximo sprint -1.5kg)
+            let correction = self.compensation.correction((accumulated_error - rounding).kg(), base_weight.kg(), remaining_sprints);
+            let correction = Mass::from_kg(if correction.is_finite() { correction } else { 0.0 }) + rounding;
+            // não pode ser negativo nem sair da dose mínima/máxima da linha;
+            // o que não couber continua no erro acumulado e volta depois
+            let wanted = base_weight - correction;
+            let (min, max) = self.dose_limits(&id);
+            let clamp = |m: Mass| match max {
+                Some(max) => m.max(min).min(max),
+                None => m.max(min),
+            };
+            let ideal = clamp(wanted);
+            let deferral = if wanted < ideal {
+                Some(Deferral::BelowMinimum)
+            } else if wanted > ideal {
+                Some(Deferral::AboveMaximum)
+            } else {
+                None
+            };
+            // arredonda à divisão da balança; o resíduo fica para o próximo
+            // sprint. Se o arredondamento sair dos limites, volta uma divisão
+            // para dentro, sem deixar a grade da balança
+            let step = self.resolution(&id);
+            let mut target = ideal.round_to(step);
+            if target < min {
+                target += step;
+            } else if max.is_some_and(|max| target > max) {
+                target -= step;
             }
-            // Clamp para evitar números absurdos (não pode ser negativo)
-            suggested_next = suggested_next.max(0.0).min(1e12);
 
-            suggestions.insert(id, suggested_next);
+            suggestions.insert(id, Suggestion { target, rounding: target - ideal, deferred: wanted - ideal, deferral });
         }
         suggestions
     }
 
     pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("processos")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-        Ok(())
+        index::save(db, self)
     }
 
     pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("processos")?;
-        tree.remove(id.as_bytes())?;
+        index::delete::<Processo>(db, id)
+    }
+
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<Processo>, Box<dyn std::error::Error>> {
+        index::get(db, id)
+    }
+
+    /// Processos ainda não finalizados criados a partir da fórmula informada.
+    pub fn find_open_by_formula(formula_id: &str, db: &sled::Db) -> Result<Vec<Processo>, Box<dyn std::error::Error>> {
+        let processos: Vec<Processo> = index::load_by(db, "processos_by_formula", formula_id)?;
+        Ok(processos.into_iter().filter(|p| !p.status.is_closed()).collect())
+    }
+
+    pub fn get_all(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<Processo>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, sort)
+    }
+
+    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Page<Processo>, Box<dyn std::error::Error>> {
+        search::search(db, name, |r: &Processo| &r.nome, page, page_size)
+    }
+
+    /// Muda a situação do processo conforme a tabela de transições e registra
+    /// quem fez, quando e por quê. Pausar e cancelar exigem um motivo.
+    pub fn transition(&mut self, to: ProcessoStatus, user: &User, reason: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.status.can_transition(to) {
+            return Err(format!("Transição de processo inválida: {:?} → {:?}", self.status, to).into());
+        }
+        let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+        if to.requires_reason() && reason.is_none() {
+            return Err("Informe o motivo".into());
+        }
+        self.status_history.push(ProcessoStatusChange {
+            from: self.status,
+            to,
+            reason,
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            created_at: Utc::now(),
+        });
+        self.status = to;
+        self.touch();
         Ok(())
     }
 
-    pub fn get_all(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Processo>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("processos")?;
-        let start = page * page_size;
-        let mut processos = Vec::new();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_key, value) = result?;
-            let processo: Processo = serde_json::from_slice(&value)?;
-            processos.push(processo);
-        }
-        Ok(processos)
-    }
-
-    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Processo>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("processos")?;
-        let mut processos = Vec::new();
-        let start = page * page_size;
-        let name_lower = name.to_lowercase();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let processo: Processo = serde_json::from_slice(&value)?;
-            if processo.nome.to_lowercase().contains(&name_lower) {
-                processos.push(processo);
-            }
+    /// Confere se o processo aceita sprints; um processo planejado passa a
+    /// andamento no primeiro sprint.
+    pub fn start_sprint(&mut self, user: &User) -> Result<(), Box<dyn std::error::Error>> {
+        match self.status {
+            ProcessoStatus::InProgress => Ok(()),
+            ProcessoStatus::Planned => self.transition(ProcessoStatus::InProgress, user, None),
+            status => Err(format!("Processo {:?} não aceita sprints", status).into()),
         }
-        Ok(processos)
     }
+}
 
-    pub fn update_status(&mut self, new_status: String) {
-        self.status = new_status;
-        self.touch();
+impl Indexed for Processo {
+    const TREE: &'static str = "processos";
+    const INDEXES: &'static [&'static str] = &["processos_by_formula", "processos_by_name", "processos_by_created", "processos_by_updated", "processos_by_status"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        vec![
+            IndexEntry::multi("processos_by_formula", &self.formula.id, &self.id),
+            IndexEntry::by_name("processos_by_name", &self.nome, &self.id),
+            IndexEntry::by_time("processos_by_created", &self.created_at, &self.id),
+            IndexEntry::by_time("processos_by_updated", &self.updated_at, &self.id),
+            IndexEntry::multi("processos_by_status", self.status.key(), &self.id),
+        ]
+    }
+
+    fn sort_index(field: SortField) -> Option<&'static str> {
+        match field {
+            SortField::Name => Some("processos_by_name"),
+            SortField::CreatedAt => Some("processos_by_created"),
+            SortField::UpdatedAt => Some("processos_by_updated"),
+            SortField::Status => Some("processos_by_status"),
+        }
     }
 }
 
@@ -161,33 +510,265 @@ mod tests {
     use crate::models::item::Item;
     use crate::models::user::{User, Role};
     use crate::models::sprint::{Sprint, SprintItem};
+    use crate::models::formula::{DoseLimits, Formula};
+    use crate::models::resolver::Resolver;
 
-    #[test]
-    fn test_suggest_next_sprint_targets_per_sprint() {
+    fn kg(v: f64) -> Mass {
+        Mass::from_kg(v)
+    }
+
+    /// Processo com A=30 e B=20 por sprint, um sprint concluído (A=31,5; B=19)
+    /// e um anulado que não pode pesar nas sugestões.
+    fn scenario(compensation: CompensationStrategy) -> (Processo, String, String) {
+        let db = sled::Config::default().temporary(true).open().unwrap();
         // cria fórmula com peso por sprint: A=30, B=20
         let mut formula = Formula::new("F".to_string(), vec![]);
         let f = Fornecedor::new("X".to_string());
-        let item_a = Item::new("A".to_string(), f.clone());
-        let item_b = Item::new("B".to_string(), f.clone());
-        formula.add_item_by_weight(item_a.clone(), 30.0);
-        formula.add_item_by_weight(item_b.clone(), 20.0);
+        let item_a = Item::new("A".to_string(), f.id.clone());
+        let item_b = Item::new("B".to_string(), f.id.clone());
+        f.save(&db).unwrap();
+        item_a.save(&db).unwrap();
+        item_b.save(&db).unwrap();
+        formula.add_item_by_weight(&item_a.id, 30.0);
+        formula.add_item_by_weight(&item_b.id, 20.0);
+        let snapshot = Resolver::new(&db).formula(&formula).unwrap();
+
+        let mut processo = Processo::new("P".to_string(), snapshot, 0.0);
+        processo.compensation = compensation;
+        let op = User::new("op".to_string(), "pw".to_string(), Role::User).unwrap();
+
+        let mut si_a = SprintItem::new(item_a.id.clone(), kg(30.0));
+        si_a.set_actual(kg(31.5));
+        let mut si_b = SprintItem::new(item_b.id.clone(), kg(20.0));
+        si_b.set_actual(kg(19.0));
+
+        let mut sprint = Sprint::new(processo.id.clone(), 1, vec![si_a, si_b], op.id.clone());
+        sprint.complete(None, false).unwrap();
+        processo.add_sprint(sprint);
+
+        // um segundo sprint anulado não altera as sugestões
+        let mut si_a = SprintItem::new(item_a.id.clone(), kg(28.5));
+        si_a.set_actual(kg(40.0));
+        let mut anulado = Sprint::new(processo.id.clone(), 2, vec![si_a], op.id.clone());
+        anulado.complete(None, false).unwrap();
+        let anulado_id = anulado.id.clone();
+        processo.add_sprint(anulado);
+        processo.sprint_mut(&anulado_id).unwrap().void(&op.id, "balança descalibrada").unwrap();
+
+        (processo, item_a.id, item_b.id)
+    }
+
+    fn assert_suggestions(processo: &Processo, a_id: &str, b_id: &str, a: f64, b: f64) {
+        let suggestions = processo.suggest_next_sprint_targets();
+        let got_a = suggestions[a_id].target.kg();
+        let got_b = suggestions[b_id].target.kg();
+        assert!((got_a - a).abs() < 1e-6, "A sugerido {} != {}", got_a, a);
+        assert!((got_b - b).abs() < 1e-6, "B sugerido {} != {}", got_b, b);
+    }
+
+    #[test]
+    fn test_suggest_next_sprint_targets_per_sprint() {
+        let (processo, a, b) = scenario(CompensationStrategy::FullCarry);
+        assert_suggestions(&processo, &a, &b, 28.5, 21.0);
+    }
+
+    #[test]
+    fn test_suggest_spreads_error_over_remaining_sprints() {
+        let (mut processo, a, b) = scenario(CompensationStrategy::Spread);
+        // sem plano, a correção vai inteira no próximo sprint
+        assert_suggestions(&processo, &a, &b, 28.5, 21.0);
+        // 4 sprints planejados, 1 feito: o erro se divide pelos 3 restantes
+        processo.set_plan(None, Some(4)).unwrap();
+        assert_suggestions(&processo, &a, &b, 29.5, 20.0 + 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_suggest_caps_correction_per_sprint() {
+        let (processo, a, b) = scenario(CompensationStrategy::Capped { max_pct: 2.0 });
+        assert_suggestions(&processo, &a, &b, 29.4, 20.4);
+    }
+
+    #[test]
+    fn test_suggest_without_compensation_keeps_base() {
+        let (processo, a, b) = scenario(CompensationStrategy::None);
+        assert_suggestions(&processo, &a, &b, 30.0, 20.0);
+    }
+
+    #[test]
+    fn test_status_transitions_and_legacy_values() {
+        let legado: ProcessoStatus = serde_json::from_str("\"Em Andamento\"").unwrap();
+        assert_eq!(legado, ProcessoStatus::InProgress);
+        let legado: ProcessoStatus = serde_json::from_str("\"Terminado\"").unwrap();
+        assert_eq!(legado, ProcessoStatus::Finished);
+
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let formula = Resolver::new(&db).formula(&Formula::new("F".to_string(), vec![])).unwrap();
+        let sup = User::new("sup".to_string(), "pw".to_string(), Role::Supervisor).unwrap();
+        let mut processo = Processo::new("P".to_string(), formula, 0.0);
+        assert_eq!(processo.status, ProcessoStatus::Planned);
+
+        processo.start_sprint(&sup).unwrap();
+        assert_eq!(processo.status, ProcessoStatus::InProgress);
+        assert!(processo.transition(ProcessoStatus::Paused, &sup, Some(" ".to_string())).is_err());
+        processo.transition(ProcessoStatus::Paused, &sup, Some("falta de matéria-prima".to_string())).unwrap();
+        assert!(processo.start_sprint(&sup).is_err());
+        assert!(processo.transition(ProcessoStatus::Finished, &sup, None).is_err());
+
+        processo.transition(ProcessoStatus::InProgress, &sup, None).unwrap();
+        processo.transition(ProcessoStatus::Finished, &sup, None).unwrap();
+        assert!(processo.transition(ProcessoStatus::Cancelled, &sup, Some("x".to_string())).is_err());
+        assert!(processo.start_sprint(&sup).is_err());
+
+        let motivos: Vec<_> = processo.status_history.iter().map(|c| (c.to, c.reason.as_deref())).collect();
+        assert_eq!(motivos, vec![
+            (ProcessoStatus::InProgress, None),
+            (ProcessoStatus::Paused, Some("falta de matéria-prima")),
+            (ProcessoStatus::InProgress, None),
+            (ProcessoStatus::Finished, None),
+        ]);
+    }
+
+    #[test]
+    fn test_plan_is_derived_from_mass_or_sprint_count() {
+        // fórmula de 50 kg por sprint
+        let plan = ProcessoPlan::derive(50.0, Some(500.0), None, None).unwrap().unwrap();
+        assert_eq!((plan.planned_sprints, plan.scale), (10, 1.0));
+
+        // misturador de 120 kg: 5 sprints de 100 kg
+        let plan = ProcessoPlan::derive(50.0, Some(500.0), None, Some(120.0)).unwrap().unwrap();
+        assert_eq!((plan.planned_sprints, plan.scale), (5, 2.0));
+
+        let plan = ProcessoPlan::derive(50.0, None, Some(4), None).unwrap().unwrap();
+        assert_eq!((plan.target_mass, plan.scale), (200.0, 1.0));
+
+        assert!(ProcessoPlan::derive(50.0, Some(500.0), Some(2), Some(120.0)).is_err());
+        assert!(ProcessoPlan::derive(50.0, None, Some(0), None).is_err());
+        assert_eq!(ProcessoPlan::derive(50.0, None, None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_progress_and_scaled_suggestions_follow_plan() {
+        let (mut processo, a, b) = scenario(CompensationStrategy::Spread);
+        // A=30, B=20 → 2 sprints de 100 kg (escala 2)
+        processo.set_plan(Some(200.0), Some(2)).unwrap();
+        assert_eq!(processo.weight, 200.0);
+        assert_eq!(processo.remaining_sprints(), Some(1));
+
+        // erro contra a base escalada: A=31,5-60, B=19-40, todo no último sprint
+        assert_suggestions(&processo, &a, &b, 88.5, 61.0);
+
+        let progress = processo.progress();
+        assert_eq!(progress.sprints_done, 1);
+        assert_eq!(progress.planned_sprints, Some(2));
+        assert_eq!(progress.mass_produced, 50.5);
+        assert!(!progress.complete);
+    }
+
+    #[test]
+    fn test_suggestions_round_to_resolution_and_carry_residue() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let f = Fornecedor::new("X".to_string());
+        f.save(&db).unwrap();
+        let item = Item::new("A".to_string(), f.id.clone());
+        item.save(&db).unwrap();
+        let mut formula = Formula::new("F".to_string(), vec![]);
+        formula.add_item_by_weight(&item.id, 30.02);
+        let snapshot = Resolver::new(&db).formula(&formula).unwrap();
+        // balança de 50 g e sem compensação: só o arredondamento é devolvido
+        let mut processo = Processo::new("P".to_string(), snapshot, 0.0);
+        processo.compensation = CompensationStrategy::None;
+        processo.resolution = Some(kg(0.05));
 
-        // processo com 1 sprint já executado e 1 restante (total_sprints = 2)
-        let mut processo = Processo::new("P".to_string(), formula, "ok".to_string(), 0.0);
-        let op = User::new("op".to_string(), "pw".to_string(), Role::User);
+        let mut alvos = Vec::new();
+        for numero in 1..=4 {
+            let sugestoes = processo.suggest_next_sprint_targets();
+            let mut sprint = Sprint::new(processo.id.clone(), numero, vec![SprintItem::new(item.id.clone(), kg(0.0))], "op".to_string());
+            sprint.apply_suggestions(&sugestoes);
+            let alvo = sprint.itens[0].target;
+            sprint.record_actual(&item.id, alvo).unwrap();
+            sprint.complete(None, false).unwrap();
+            processo.add_sprint(sprint);
+            alvos.push(alvo.kg());
+        }
+        assert_eq!(alvos, vec![30.0, 30.05, 30.0, 30.05]);
+        // o total dosado nunca se afasta do teórico mais que meia divisão
+        let desvio = processo.accumulate_divergences()[&item.id];
+        assert!(desvio.kg().abs() <= 0.025, "desvio {}", desvio);
+    }
 
-        let mut si_a = SprintItem::new(item_a.clone(), 30.0);
-        si_a.set_actual(31.5);
-        let mut si_b = SprintItem::new(item_b.clone(), 20.0);
-        si_b.set_actual(19.0);
+    #[test]
+    fn test_abort_sprint_rejected_on_closed_processo() {
+        let (mut processo, a, _b) = scenario(CompensationStrategy::FullCarry);
+        let total = processo.sprints.len();
+        let processo_id = processo.id.clone();
+        let aberto = || Sprint::new(processo_id.clone(), 3, vec![SprintItem::new(a.clone(), kg(28.5))], "op".to_string());
+
+        let sprint = aberto();
+        processo.status = ProcessoStatus::Cancelled;
+        assert!(processo.abort_sprint(sprint, "op", None).is_err());
+        assert_eq!(processo.sprints.len(), total);
 
-        let sprint = Sprint::new(processo.id.clone(), 1, vec![si_a, si_b], op);
+        let sprint = aberto();
+        processo.status = ProcessoStatus::InProgress;
+        processo.abort_sprint(sprint, "op", Some("faltou material".to_string())).unwrap();
+        assert_eq!(processo.sprints.len(), total + 1);
+    }
+
+    #[test]
+    fn test_skipped_item_counts_as_full_deficit() {
+        let (mut processo, a, b) = scenario(CompensationStrategy::FullCarry);
+        // segundo sprint: A pesado no alvo, B esquecido
+        let mut sprint = Sprint::new(processo.id.clone(), 3, vec![SprintItem::new(a.clone(), kg(28.5)), SprintItem::new(b.clone(), kg(21.0))], "op".to_string());
+        sprint.record_actual(&a, kg(28.5)).unwrap();
+        assert!(sprint.complete(None, false).is_err());
+        sprint.complete(None, true).unwrap();
+        assert!(sprint.itens[1].skipped);
         processo.add_sprint(sprint);
 
-        let suggestions = processo.suggest_next_sprint_targets(1);
-        let a = suggestions.get(&item_a.id).unwrap();
-        let b = suggestions.get(&item_b.id).unwrap();
-        assert!((*a - 28.5).abs() < 1e-6, "A sugerido {} != 28.5", a);
-        assert!((*b - 21.0).abs() < 1e-6, "B sugerido {} != 21.0", b);
+        // B: -1 do primeiro sprint e -20 do pulado
+        assert_eq!(processo.accumulate_divergences()[&b], kg(-21.0));
+        assert_suggestions(&processo, &a, &b, 30.0, 41.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dose_limits_defer_correction() {
+        let (mut processo, a, b) = scenario(CompensationStrategy::FullCarry);
+        processo.formula.itens[0].limits = DoseLimits { min: Some(29.0), max: None };
+        processo.formula.itens[1].limits = DoseLimits { min: None, max: Some(20.5) };
+
+        // A queria 28,5 e B queria 21: o que passa dos limites fica para depois
+        let sugestoes = processo.suggest_next_sprint_targets();
+        assert_eq!(sugestoes[&a].target, kg(29.0));
+        assert_eq!(sugestoes[&a].deferred, kg(-0.5));
+        assert_eq!(sugestoes[&a].deferral, Some(Deferral::BelowMinimum));
+        assert_eq!(sugestoes[&b].target, kg(20.5));
+        assert_eq!(sugestoes[&b].deferred, kg(0.5));
+        assert_eq!(sugestoes[&b].deferral, Some(Deferral::AboveMaximum));
+
+        let mut sprint = Sprint::new(processo.id.clone(), 3, vec![SprintItem::new(a.clone(), kg(29.0)), SprintItem::new(b.clone(), kg(20.5))], "op".to_string());
+        sprint.record_actual(&a, kg(29.0)).unwrap();
+        sprint.record_actual(&b, kg(20.5)).unwrap();
+        sprint.complete(None, false).unwrap();
+        processo.add_sprint(sprint);
+
+        // o adiado volta no sprint seguinte, agora dentro dos limites
+        let sugestoes = processo.suggest_next_sprint_targets();
+        assert_eq!(sugestoes[&a].target, kg(29.5));
+        assert_eq!(sugestoes[&b].target, kg(20.5));
+        assert!(sugestoes.values().all(|s| s.deferral.is_none() && s.deferred == Mass::ZERO));
+
+        // limite fora da grade de 50 g: o alvo fica na divisão seguinte
+        processo.resolution = Some(kg(0.05));
+        processo.formula.itens[0].limits = DoseLimits { min: Some(29.52), max: None };
+        processo.formula.itens[1].limits = DoseLimits { min: None, max: Some(20.48) };
+        let sugestoes = processo.suggest_next_sprint_targets();
+        assert_eq!(sugestoes[&a].target, kg(29.55));
+        assert_eq!(sugestoes[&b].target, kg(20.45));
+
+        // o plano dobra a dose de A (60 kg), acima da máxima da linha
+        processo.formula.itens[0].limits = DoseLimits { min: None, max: Some(35.0) };
+        assert!(processo.set_plan(Some(200.0), Some(2)).is_err());
+        assert!(processo.plan.is_none());
+        processo.set_plan(Some(100.0), Some(2)).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::cell::Cell;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::models::index::Indexed;
+
+const MG_PER_KG: f64 = 1_000_000.0;
+
+/// Massa em miligramas inteiros. Somas e diferenças entre sprints são feitas
+/// em inteiros, sem o ruído acumulado do ponto flutuante. Trafega e é
+/// gravada sempre em mg inteiros; os registros antigos, em kg decimais, são
+/// convertidos uma vez por `ensure_mass_in_mg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Mass(i64);
+
+impl Mass {
+    pub const ZERO: Mass = Mass(0);
+
+    pub fn from_kg(kg: f64) -> Self {
+        Mass((kg * MG_PER_KG).round() as i64)
+    }
+
+    pub fn kg(self) -> f64 {
+        self.0 as f64 / MG_PER_KG
+    }
+
+    /// Múltiplo de `resolution` mais próximo; no empate, para longe do zero.
+    pub fn round_to(self, resolution: Mass) -> Mass {
+        let step = resolution.0.abs();
+        if step <= 1 {
+            return self;
+        }
+        let half = step / 2;
+        let steps = if self.0 >= 0 { (self.0 + half) / step } else { (self.0 - half) / step };
+        Mass(steps * step)
+    }
+
+    pub fn max(self, other: Mass) -> Mass {
+        Mass(self.0.max(other.0))
+    }
+}
+
+impl Add for Mass {
+    type Output = Mass;
+    fn add(self, rhs: Mass) -> Mass {
+        Mass(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Mass {
+    type Output = Mass;
+    fn sub(self, rhs: Mass) -> Mass {
+        Mass(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Mass {
+    type Output = Mass;
+    fn neg(self) -> Mass {
+        Mass(-self.0)
+    }
+}
+
+impl Mul<usize> for Mass {
+    type Output = Mass;
+    fn mul(self, rhs: usize) -> Mass {
+        Mass(self.0 * rhs as i64)
+    }
+}
+
+impl AddAssign for Mass {
+    fn add_assign(&mut self, rhs: Mass) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for Mass {
+    fn sub_assign(&mut self, rhs: Mass) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Sum for Mass {
+    fn sum<I: Iterator<Item = Mass>>(iter: I) -> Mass {
+        Mass(iter.map(|m| m.0).sum())
+    }
+}
+
+impl fmt::Display for Mass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3} kg", self.kg())
+    }
+}
+
+impl Serialize for Mass {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mass {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MassVisitor)
+    }
+}
+
+thread_local! {
+    static LEGACY_KG: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Lê em `read` registros gravados quando as massas eram kg decimais. Fora
+/// daqui, só mg inteiros são aceitos.
+pub(crate) fn reading_legacy_kg<T>(read: impl FnOnce() -> T) -> T {
+    LEGACY_KG.with(|legacy| legacy.set(true));
+    let value = read();
+    LEGACY_KG.with(|legacy| legacy.set(false));
+    value
+}
+
+/// Sempre mg inteiros; decimais (kg) só na leitura de registros antigos.
+struct MassVisitor;
+
+impl Visitor<'_> for MassVisitor {
+    type Value = Mass;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("massa em mg inteiros")
+    }
+
+    fn visit_i64<E: de::Error>(self, mg: i64) -> Result<Mass, E> {
+        Ok(Mass(mg))
+    }
+
+    fn visit_u64<E: de::Error>(self, mg: u64) -> Result<Mass, E> {
+        i64::try_from(mg).map(Mass).map_err(|_| E::custom("Peso inválido"))
+    }
+
+    fn visit_f64<E: de::Error>(self, kg: f64) -> Result<Mass, E> {
+        if !LEGACY_KG.with(|legacy| legacy.get()) {
+            return Err(E::custom("Massa deve ser informada em mg inteiros"));
+        }
+        if !kg.is_finite() {
+            return Err(E::custom("Peso inválido"));
+        }
+        Ok(Mass::from_kg(kg))
+    }
+}
+
+/// Regrava em mg os registros do tipo gravados com massas em kg.
+fn rewrite_in_mg<T: Indexed>(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+    let tree = db.open_tree(T::TREE)?;
+    for result in tree.iter() {
+        let (id, value) = result?;
+        let record: T = reading_legacy_kg(|| serde_json::from_slice(&value))?;
+        tree.insert(id, serde_json::to_vec(&record)?)?;
+    }
+    Ok(())
+}
+
+/// Converte uma única vez os bancos gravados antes das massas em mg. Roda
+/// antes dos índices, que já leem os registros em mg.
+pub(crate) fn ensure_mass_in_mg(db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::models::{item::Item, processo::Processo, sprint::Sprint};
+
+    let meta = db.open_tree("meta")?;
+    if meta.get("mass_unit")?.is_some_and(|unit| unit.as_ref() == b"mg") {
+        return Ok(());
+    }
+    rewrite_in_mg::<Item>(db)?;
+    rewrite_in_mg::<Processo>(db)?;
+    rewrite_in_mg::<Sprint>(db)?;
+    meta.insert("mass_unit", "mg")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mass_is_exact_and_rounds_to_resolution() {
+        // 0,1 kg somado 30 vezes não dá 3 kg em f64
+        let soma: Mass = std::iter::repeat_n(Mass::from_kg(0.1), 30).sum();
+        assert_eq!(soma, Mass::from_kg(3.0));
+        assert_eq!(serde_json::to_string(&soma).unwrap(), "3000000");
+        assert_eq!(serde_json::from_str::<Mass>("3000000").unwrap(), soma);
+        assert_eq!(serde_json::from_str::<Mass>("-250").unwrap(), -Mass::from_kg(0.00025));
+        // `1` é sempre 1 mg; `1.0` não é aceito fora da leitura de registros antigos
+        assert_eq!(serde_json::from_str::<Mass>("1").unwrap(), Mass::from_kg(0.000001));
+        assert!(serde_json::from_str::<Mass>("1.0").is_err());
+        let legado = |json: &str| reading_legacy_kg(|| serde_json::from_str::<Mass>(json)).unwrap();
+        assert_eq!(legado("3.0"), soma);
+        assert_eq!(legado("28.4999999"), Mass::from_kg(28.5));
+        assert!(serde_json::from_str::<Mass>("3.0").is_err());
+
+        let div = Mass::from_kg(0.05);
+        assert_eq!(Mass::from_kg(28.47).round_to(div), Mass::from_kg(28.45));
+        assert_eq!(Mass::from_kg(28.475).round_to(div), Mass::from_kg(28.5));
+        assert_eq!(Mass::from_kg(-0.026).round_to(div), Mass::from_kg(-0.05));
+        assert_eq!(Mass::from_kg(1.2345).round_to(Mass::ZERO), Mass::from_kg(1.2345));
+    }
+
+    #[test]
+    fn test_legacy_records_are_rewritten_in_mg() {
+        use crate::models::sprint::{Sprint, SprintItem};
+
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let sprint = Sprint::new("p1".to_string(), 1, vec![SprintItem::new("a".to_string(), Mass::from_kg(30.0))], "op".to_string());
+        // rascunho gravado quando o alvo era kg decimal
+        let mut json = serde_json::to_value(&sprint).unwrap();
+        json["itens"][0]["target"] = serde_json::json!(30.0);
+        db.open_tree("sprints").unwrap().insert(sprint.id.as_bytes(), serde_json::to_vec(&json).unwrap()).unwrap();
+        assert!(Sprint::get_by_id(&sprint.id, &db).is_err());
+
+        // a conversão roda uma vez; rodar de novo não muda nada
+        ensure_mass_in_mg(&db).unwrap();
+        ensure_mass_in_mg(&db).unwrap();
+        assert_eq!(Sprint::get_by_id(&sprint.id, &db).unwrap().unwrap().itens[0].target, Mass::from_kg(30.0));
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use crate::models::processo::Processo;
+use crate::models::user::User;
+use crate::models::quantity::Mass;
+
+/// Fechamento de um item do lote.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReconciliationLine {
+    pub item_id: String,
+    pub item_nome: String,
+    /// Peso do item no sprint × sprints concluídos.
+    pub theoretical: Mass,
+    /// Total efetivamente dosado nos sprints concluídos.
+    pub actual: Mass,
+    /// Erro que ficou sem compensar (`actual - theoretical`).
+    pub residual: Mass,
+    /// Participação do item na fórmula, em %.
+    pub expected_pct: f64,
+    /// Participação do item no que foi dosado, em %.
+    pub actual_pct: f64,
+}
+
+/// Aprovação do lote pela qualidade.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QaSignOff {
+    pub user_id: String,
+    pub username: String,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Relatório de fechamento do lote: teórico × real por item e composição
+/// final frente às proporções da fórmula.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reconciliation {
+    pub sprints_completed: usize,
+    pub lines: Vec<ReconciliationLine>,
+    pub total_theoretical: Mass,
+    pub total_actual: Mass,
+    pub generated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub sign_off: Option<QaSignOff>,
+}
+
+impl Reconciliation {
+    /// Monta o relatório a partir dos sprints concluídos e não anulados.
+    pub fn build(processo: &Processo) -> Self {
+        let sprints: Vec<_> = processo.sprints.iter().filter(|s| s.counts()).collect();
+        let total_actual: Mass = sprints.iter()
+            .flat_map(|s| s.itens.iter().filter_map(|it| it.actual))
+            .sum();
+
+        let lines: Vec<ReconciliationLine> = processo.formula.get_proportions().into_iter().map(|p| {
+            let theoretical = processo.base_weight(&p.item_id) * sprints.len();
+            let actual: Mass = sprints.iter()
+                .flat_map(|s| s.itens.iter())
+                .filter(|it| it.item_id == p.item_id)
+                .filter_map(|it| it.actual)
+                .sum();
+            ReconciliationLine {
+                item_nome: processo.formula.item(&p.item_id).map(|fi| fi.item.nome.clone()).unwrap_or_default(),
+                item_id: p.item_id,
+                theoretical,
+                actual,
+                residual: actual - theoretical,
+                expected_pct: p.proporcao * 100.0,
+                actual_pct: if total_actual > Mass::ZERO { actual.kg() / total_actual.kg() * 100.0 } else { 0.0 },
+            }
+        }).collect();
+
+        Reconciliation {
+            sprints_completed: sprints.len(),
+            total_theoretical: lines.iter().map(|l| l.theoretical).sum(),
+            total_actual,
+            lines,
+            generated_at: Utc::now(),
+            sign_off: None,
+        }
+    }
+
+    /// Registra a aprovação da qualidade; o lote só é aprovado uma vez.
+    pub fn sign_off(&mut self, user: &User, comment: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.sign_off.is_some() {
+            return Err("Lote já aprovado pela qualidade".into());
+        }
+        self.sign_off = Some(QaSignOff {
+            user_id: user.id.clone(),
+            username: user.username.clone(),
+            comment: comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fornecedor::Fornecedor;
+    use crate::models::item::Item;
+    use crate::models::formula::Formula;
+    use crate::models::resolver::Resolver;
+    use crate::models::sprint::{Sprint, SprintItem};
+    use crate::models::user::Role;
+
+    #[test]
+    fn test_reconciliation_compares_theoretical_and_actual() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let f = Fornecedor::new("X".to_string());
+        f.save(&db).unwrap();
+        let a = Item::new("A".to_string(), f.id.clone());
+        let b = Item::new("B".to_string(), f.id.clone());
+        a.save(&db).unwrap();
+        b.save(&db).unwrap();
+        let mut formula = Formula::new("F".to_string(), vec![]);
+        formula.add_item_by_weight(&a.id, 30.0);
+        formula.add_item_by_weight(&b.id, 20.0);
+        let mut processo = Processo::new("P".to_string(), Resolver::new(&db).formula(&formula).unwrap(), 50.0);
+
+        for (numero, (peso_a, peso_b)) in [(31.5, 19.0), (28.5, 21.0)].into_iter().enumerate() {
+            let mut itens = vec![SprintItem::new(a.id.clone(), Mass::from_kg(30.0)), SprintItem::new(b.id.clone(), Mass::from_kg(20.0))];
+            itens[0].set_actual(Mass::from_kg(peso_a));
+            itens[1].set_actual(Mass::from_kg(peso_b));
+            let mut sprint = Sprint::new(processo.id.clone(), numero + 1, itens, "op".to_string());
+            sprint.complete(None, false).unwrap();
+            processo.add_sprint(sprint);
+        }
+        // sprint abortado não entra no fechamento
+        let mut abortado = Sprint::new(processo.id.clone(), 3, vec![SprintItem::new(a.id.clone(), Mass::from_kg(30.0))], "op".to_string());
+        abortado.record_actual(&a.id, Mass::from_kg(5.0)).unwrap();
+        abortado.abort("op", None).unwrap();
+        processo.add_sprint(abortado);
+
+        let mut rec = Reconciliation::build(&processo);
+        assert_eq!(rec.sprints_completed, 2);
+        assert_eq!(rec.total_theoretical, Mass::from_kg(100.0));
+        assert_eq!(rec.total_actual, Mass::from_kg(100.0));
+        let linha_a = &rec.lines[0];
+        assert_eq!((linha_a.item_nome.as_str(), linha_a.theoretical.kg(), linha_a.actual.kg(), linha_a.residual), ("A", 60.0, 60.0, Mass::ZERO));
+        assert!((linha_a.expected_pct - 60.0).abs() < 1e-9);
+        assert!((linha_a.actual_pct - 60.0).abs() < 1e-9);
+
+        let qa = User::new("qa".to_string(), "pw".to_string(), Role::Quality).unwrap();
+        rec.sign_off(&qa, Some("ok".to_string())).unwrap();
+        assert!(rec.sign_off(&qa, None).is_err());
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::fornecedor::Fornecedor;
+use crate::models::item::Item;
+use crate::models::formula::{Formula, ItemProporcao, Tolerance, DoseLimits};
+use crate::models::sprint::{Addition, Sprint, SprintStatus, ToleranceOverride, ToleranceStatus};
+use crate::models::processo::{Processo, ProcessoPlan, ProcessoProgress, ProcessoStatus, ProcessoStatusChange};
+use crate::models::user::{User, UserInfo};
+use crate::models::page::Page;
+use crate::models::compensation::{CompensationStrategy, RatioAdjustment};
+use crate::models::reconciliation::Reconciliation;
+use crate::models::unit::{self, Unit};
+use crate::models::quantity::Mass;
+
+/// Lê uma referência gravada como id ou, nos bancos antigos, como o objeto
+/// embutido inteiro (de onde só o `id` é aproveitado).
+pub fn deserialize_ref<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Ref {
+        Id(String),
+        Embedded { id: String },
+    }
+    Ok(match Ref::deserialize(deserializer)? {
+        Ref::Id(id) | Ref::Embedded { id } => id,
+    })
+}
+
+/// Item com o fornecedor resolvido, no formato esperado pelo frontend.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ItemView {
+    pub id: String,
+    pub nome: String,
+    pub fornecedor_id: String,
+    #[serde(default)]
+    pub fornecedor: Option<Fornecedor>,
+    #[serde(default)]
+    pub unit: Unit,
+    #[serde(default)]
+    pub density: Option<f64>,
+    #[serde(default)]
+    pub resolution: Option<Mass>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ItemFormulaView {
+    pub item: ItemView,
+    pub peso: f64,
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    #[serde(default)]
+    pub unit: Unit,
+    #[serde(default = "unit::kg_default_factor")]
+    pub factor: f64,
+    #[serde(default)]
+    pub limits: DoseLimits,
+}
+
+/// Fórmula com os itens resolvidos. É também a cópia que o processo guarda
+/// ao ser criado, por isso tem o mesmo formato da fórmula embutida antiga.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FormulaView {
+    pub id: String,
+    pub nome: String,
+    pub itens: Vec<ItemFormulaView>,
+    /// Versão da fórmula; na cópia guardada pelo processo, a versão fixada.
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub approved_by: Option<String>,
+    #[serde(default)]
+    pub approved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+impl FormulaView {
+    pub fn item(&self, item_id: &str) -> Option<&ItemFormulaView> {
+        self.itens.iter().find(|fi| fi.item.id == item_id)
+    }
+
+    /// Converte para kg uma quantidade informada na unidade da linha.
+    pub fn to_kg(&self, item_id: &str, quantity: f64) -> f64 {
+        quantity * self.item(item_id).map_or(1.0, |fi| fi.factor)
+    }
+
+    /// Massa de um sprint pela fórmula.
+    pub fn total_weight(&self) -> f64 {
+        self.itens.iter().map(|fi| fi.peso).sum()
+    }
+
+    /// Proporção de cada item, como `Formula::get_proportions`.
+    pub fn get_proportions(&self) -> Vec<ItemProporcao> {
+        let total = self.total_weight();
+        self.itens.iter().map(|fi| ItemProporcao {
+            item_id: fi.item.id.clone(),
+            proporcao: if total > 0.0 { fi.peso / total } else { 0.0 },
+        }).collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SprintItemView {
+    pub item: ItemView,
+    pub target: Mass,
+    pub actual: Option<Mass>,
+    pub additions: Vec<Addition>,
+    pub skipped: bool,
+    pub tolerance: Tolerance,
+    pub tolerance_status: ToleranceStatus,
+    /// Unidade da linha; `target` e `actual` continuam em kg.
+    pub unit: Unit,
+    pub factor: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SprintView {
+    pub id: String,
+    pub processo_id: String,
+    pub numero: usize,
+    pub itens: Vec<SprintItemView>,
+    pub operador_id: String,
+    pub operador: Option<UserInfo>,
+    pub comentario: Option<String>,
+    pub status: SprintStatus,
+    pub status_reason: Option<String>,
+    pub status_changed_by: Option<UserInfo>,
+    pub status_changed_at: Option<DateTime<Utc>>,
+    pub tolerance_override: Option<ToleranceOverride>,
+    pub ratio_adjustment: Option<RatioAdjustment>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProcessoView {
+    pub id: String,
+    pub nome: String,
+    pub formula: FormulaView,
+    pub status: ProcessoStatus,
+    pub status_history: Vec<ProcessoStatusChange>,
+    pub weight: f64,
+    pub plan: Option<ProcessoPlan>,
+    pub progress: ProcessoProgress,
+    pub compensation: CompensationStrategy,
+    pub mixer_capacity: Option<f64>,
+    pub reconciliation: Option<Reconciliation>,
+    pub sprints: Vec<SprintView>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Hidrata registros gravados por referência nos DTOs enviados ao frontend,
+/// guardando em cache cada entidade já carregada.
+pub struct Resolver<'a> {
+    db: &'a sled::Db,
+    fornecedores: HashMap<String, Option<Fornecedor>>,
+    itens: HashMap<String, Option<Item>>,
+    users: HashMap<String, Option<UserInfo>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(db: &'a sled::Db) -> Self {
+        Resolver { db, fornecedores: HashMap::new(), itens: HashMap::new(), users: HashMap::new() }
+    }
+
+    fn fornecedor(&mut self, id: &str) -> Result<Option<Fornecedor>, Box<dyn std::error::Error>> {
+        if !self.fornecedores.contains_key(id) {
+            let fornecedor = Fornecedor::get_by_id(id, self.db)?;
+            self.fornecedores.insert(id.to_string(), fornecedor);
+        }
+        Ok(self.fornecedores[id].clone())
+    }
+
+    fn user(&mut self, id: &str) -> Result<Option<UserInfo>, Box<dyn std::error::Error>> {
+        if !self.users.contains_key(id) {
+            let user = User::get_by_id(id, self.db)?.as_ref().map(UserInfo::from);
+            self.users.insert(id.to_string(), user);
+        }
+        Ok(self.users[id].clone())
+    }
+
+    pub fn item(&mut self, item: &Item) -> Result<ItemView, Box<dyn std::error::Error>> {
+        Ok(ItemView {
+            id: item.id.clone(),
+            nome: item.nome.clone(),
+            fornecedor_id: item.fornecedor_id.clone(),
+            fornecedor: self.fornecedor(&item.fornecedor_id)?,
+            unit: item.unit,
+            density: item.density,
+            resolution: item.resolution,
+            created_at: item.created_at,
+            updated_at: item.updated_at,
+        })
+    }
+
+    pub fn item_by_id(&mut self, id: &str) -> Result<ItemView, Box<dyn std::error::Error>> {
+        if !self.itens.contains_key(id) {
+            let item = Item::get_by_id(id, self.db)?;
+            self.itens.insert(id.to_string(), item);
+        }
+        match self.itens[id].clone() {
+            Some(item) => self.item(&item),
+            None => Err(format!("Item {} não encontrado", id).into()),
+        }
+    }
+
+    pub fn formula(&mut self, formula: &Formula) -> Result<FormulaView, Box<dyn std::error::Error>> {
+        let mut itens = Vec::new();
+        for linha in &formula.itens {
+            itens.push(ItemFormulaView {
+                item: self.item_by_id(&linha.item_id)?,
+                peso: linha.peso,
+                tolerance: linha.tolerance,
+                unit: linha.unit,
+                factor: linha.factor,
+                limits: linha.limits,
+            });
+        }
+        Ok(FormulaView {
+            id: formula.id.clone(),
+            nome: formula.nome.clone(),
+            itens,
+            version: formula.version,
+            approved_by: formula.approved_by.clone(),
+            approved_at: formula.approved_at,
+            created_at: formula.created_at,
+            updated_at: formula.updated_at,
+        })
+    }
+
+    /// Resolve os itens do sprint pela cópia da fórmula do processo, para que
+    /// o histórico mostre os nomes da época; itens fora dela vêm do cadastro.
+    pub fn sprint(&mut self, sprint: &Sprint, snapshot: &FormulaView) -> Result<SprintView, Box<dyn std::error::Error>> {
+        let mut itens = Vec::new();
+        for si in &sprint.itens {
+            let (item, unit, factor) = match snapshot.item(&si.item_id) {
+                Some(fi) => (fi.item.clone(), fi.unit, fi.factor),
+                None => (self.item_by_id(&si.item_id)?, Unit::Kg, 1.0),
+            };
+            itens.push(SprintItemView {
+                item,
+                target: si.target,
+                actual: si.actual,
+                additions: si.additions.clone(),
+                skipped: si.skipped,
+                tolerance: si.tolerance,
+                tolerance_status: si.tolerance_status(),
+                unit,
+                factor,
+            });
+        }
+        Ok(SprintView {
+            id: sprint.id.clone(),
+            processo_id: sprint.processo_id.clone(),
+            numero: sprint.numero,
+            itens,
+            operador_id: sprint.operador_id.clone(),
+            operador: self.user(&sprint.operador_id)?,
+            comentario: sprint.comentario.clone(),
+            status: sprint.status,
+            status_reason: sprint.status_reason.clone(),
+            status_changed_by: match &sprint.status_changed_by {
+                Some(id) => self.user(id)?,
+                None => None,
+            },
+            status_changed_at: sprint.status_changed_at,
+            tolerance_override: sprint.tolerance_override.clone(),
+            ratio_adjustment: sprint.ratio_adjustment.clone(),
+            created_at: sprint.created_at,
+            updated_at: sprint.updated_at,
+        })
+    }
+
+    pub fn processo(&mut self, processo: &Processo) -> Result<ProcessoView, Box<dyn std::error::Error>> {
+        let mut sprints = Vec::new();
+        for sprint in &processo.sprints {
+            sprints.push(self.sprint(sprint, &processo.formula)?);
+        }
+        Ok(ProcessoView {
+            id: processo.id.clone(),
+            nome: processo.nome.clone(),
+            formula: processo.formula.clone(),
+            status: processo.status,
+            status_history: processo.status_history.clone(),
+            weight: processo.weight,
+            plan: processo.plan,
+            progress: processo.progress(),
+            compensation: processo.compensation,
+            mixer_capacity: processo.mixer_capacity,
+            reconciliation: processo.reconciliation.clone(),
+            sprints,
+            created_at: processo.created_at,
+            updated_at: processo.updated_at,
+        })
+    }
+
+    /// Resolve todos os registros de uma página.
+    pub fn page<T, U>(
+        &mut self,
+        page: Page<T>,
+        mut resolve: impl FnMut(&mut Self, &T) -> Result<U, Box<dyn std::error::Error>>,
+    ) -> Result<Page<U>, Box<dyn std::error::Error>> {
+        let mut items = Vec::with_capacity(page.items.len());
+        for record in &page.items {
+            items.push(resolve(self, record)?);
+        }
+        Ok(Page { items, total: page.total, page: page.page, page_size: page.page_size, cursor: page.cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::auditable::Auditable;
+
+    #[test]
+    fn test_reads_legacy_embedded_references() {
+        let formula: Formula = serde_json::from_value(serde_json::json!({
+            "id": "f1", "nome": "Bolo",
+            "itens": [{ "item": { "id": "i1", "nome": "Açúcar", "fornecedor_id": "x" }, "peso": 2.5 }],
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"
+        })).unwrap();
+        assert_eq!(formula.itens[0].item_id, "i1");
+
+        let sprint: Sprint = serde_json::from_value(serde_json::json!({
+            "id": "s1", "processo_id": "p1", "numero": 1,
+            "itens": [{ "item": { "id": "i1" }, "target": 2500000, "actual": null }],
+            "operador_id": { "id": "u1", "username": "op" },
+            "comentario": null,
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"
+        })).unwrap();
+        assert_eq!(sprint.itens[0].item_id, "i1");
+        assert_eq!(sprint.operador_id, "u1");
+    }
+
+    #[test]
+    fn test_processo_snapshot_ignores_later_edits() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let fornecedor = Fornecedor::new("Usina".to_string());
+        fornecedor.save(&db).unwrap();
+        let mut item = Item::new("Açúcar".to_string(), fornecedor.id.clone());
+        item.save(&db).unwrap();
+        let mut formula = Formula::new("Bolo".to_string(), vec![]);
+        formula.add_item_by_weight(&item.id, 10.0);
+        formula.save(&db).unwrap();
+
+        let snapshot = Resolver::new(&db).formula(&formula).unwrap();
+        let processo = Processo::new("Lote 1".to_string(), snapshot, 10.0);
+        processo.save(&db).unwrap();
+
+        item.nome = "Açúcar Cristal".to_string();
+        item.update(&db).unwrap();
+        formula.itens[0].peso = 12.0;
+        formula.touch();
+        formula.save(&db).unwrap();
+
+        let atual = Resolver::new(&db).formula(&Formula::get_by_id(&formula.id, &db).unwrap().unwrap()).unwrap();
+        assert_eq!(atual.itens[0].item.nome, "Açúcar Cristal");
+        assert_eq!(atual.itens[0].item.fornecedor.as_ref().unwrap().nome, "Usina");
+
+        let view = Resolver::new(&db).processo(&Processo::get_by_id(&processo.id, &db).unwrap().unwrap()).unwrap();
+        assert_eq!(view.formula.itens[0].item.nome, "Açúcar");
+        assert_eq!(view.formula.itens[0].peso, 10.0);
+    }
+}
@@ -0,0 +1,108 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::models::index::Indexed;
+use crate::models::page::Page;
+
+/// Remove acentos e converte para minúsculas ("Açúcar" → "acucar").
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn words(text: &str) -> Vec<String> {
+    normalize(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Busca por nome: cada termo da consulta precisa ser prefixo de alguma
+/// palavra do nome, ignorando acentos e maiúsculas.
+#[derive(Debug, Clone)]
+pub struct NameQuery {
+    tokens: Vec<String>,
+}
+
+impl NameQuery {
+    pub fn parse(query: &str) -> Self {
+        NameQuery { tokens: words(query) }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        if self.tokens.is_empty() {
+            return true;
+        }
+        let name_words = words(name);
+        self.tokens.iter().all(|token| name_words.iter().any(|w| w.starts_with(token.as_str())))
+    }
+}
+
+/// Busca usada pelos `list_by_name` dos modelos: ignora acentos e
+/// maiúsculas, aceita vários termos (cada um como prefixo de alguma palavra
+/// do nome) e filtra toda a árvore do tipo antes de paginar, de modo que
+/// `total` é o número de resultados da busca.
+pub fn search<T: Indexed>(
+    db: &sled::Db,
+    query: &str,
+    name_of: impl Fn(&T) -> &str,
+    page: usize,
+    page_size: usize,
+) -> Result<Page<T>, Box<dyn std::error::Error>> {
+    let query = NameQuery::parse(query);
+    let tree = db.open_tree(T::TREE)?;
+    let mut matches = Vec::new();
+    for result in tree.iter() {
+        let (_k, value) = result?;
+        let record: T = serde_json::from_slice(&value)?;
+        if query.matches(name_of(&record)) {
+            matches.push(record);
+        }
+    }
+    Ok(Page::from_matches(matches, page, page_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_accents() {
+        assert_eq!(normalize("Açúcar Refinado"), "acucar refinado");
+        assert_eq!(normalize("AMIDO DE MILHO"), "amido de milho");
+    }
+
+    #[test]
+    fn test_prefix_and_multi_token_matching() {
+        let q = NameQuery::parse("acuc ref");
+        assert!(q.matches("Açúcar Refinado"));
+        assert!(q.matches("Refinado, açúcar"));
+        assert!(!q.matches("Açúcar Mascavo"));
+        assert!(!NameQuery::parse("car").matches("Açúcar"));
+        assert!(NameQuery::parse("  ").matches("Qualquer"));
+    }
+
+    #[test]
+    fn test_search_filters_before_paginating() {
+        use crate::models::fornecedor::Fornecedor;
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        for i in 0..25 {
+            Fornecedor::new(format!("Outro {}", i)).save(&db).unwrap();
+        }
+        for nome in ["Açúcar União", "Acucareira Central", "Doces Açúcar"] {
+            Fornecedor::new(nome.to_string()).save(&db).unwrap();
+        }
+
+        let page = Fornecedor::list_by_name("açu", &db, 0, 2).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 2);
+        let page = Fornecedor::list_by_name("acucar", &db, 1, 2).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+        let page = Fornecedor::list_by_name("doces acucar", &db, 0, 10).unwrap();
+        assert_eq!(page.total, 1);
+    }
+}
@@ -2,52 +2,236 @@ use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use uuid;
 use crate::models::auditable::Auditable;
-use crate::models::item::Item;
+use crate::models::index::{self, Indexed, IndexEntry};
+use crate::models::page::Page;
+use crate::models::resolver::deserialize_ref;
+use crate::models::formula::Tolerance;
 use crate::models::user::User;
+use crate::models::formula::ItemProporcao;
+use crate::models::compensation::{self, RatioAdjustment};
+use crate::models::quantity::Mass;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SprintItem {
-    pub item: Item,
-    pub target: f64,
-    pub actual: Option<f64>,
+    #[serde(alias = "item", deserialize_with = "deserialize_ref")]
+    pub item_id: String,
+    pub target: Mass,
+    /// Soma das adições; registros antigos só têm este valor.
+    pub actual: Option<Mass>,
+    /// Porções adicionadas, na ordem em que foram pesadas.
+    #[serde(default)]
+    pub additions: Vec<Addition>,
+    /// Item deixado de fora de propósito: conta como falta de todo o peso
+    /// base, ao contrário de um item ainda não pesado.
+    #[serde(default)]
+    pub skipped: bool,
+    /// Tolerância da linha da fórmula, copiada na criação do sprint.
+    #[serde(default)]
+    pub tolerance: Tolerance,
+    /// Quanto o arredondamento à resolução somou ao alvo sugerido; é
+    /// devolvido na sugestão do sprint seguinte.
+    #[serde(default)]
+    pub rounding: Mass,
+}
+
+/// Uma porção de um item adicionada ao sprint (um saco, um complemento...).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Addition {
+    pub id: String,
+    pub quantity: Mass,
+    #[serde(default)]
+    pub lot: Option<String>,
+    /// Balança de onde veio o peso.
+    #[serde(default)]
+    pub scale_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Addition {
+    pub fn new(quantity: Mass, lot: Option<String>, scale_id: Option<String>) -> Self {
+        let blank = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        Addition {
+            id: uuid::Uuid::new_v4().to_string(),
+            quantity,
+            lot: blank(lot),
+            scale_id: blank(scale_id),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Alvo sugerido para um item do próximo sprint, já arredondado à resolução.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion {
+    pub target: Mass,
+    /// Alvo arredondado menos o alvo calculado.
+    pub rounding: Mass,
+    /// Parte da correção que não coube na dose deste sprint e segue no erro
+    /// acumulado para os próximos: positiva é falta, negativa é excesso.
+    #[serde(default)]
+    pub deferred: Mass,
+    #[serde(default)]
+    pub deferral: Option<Deferral>,
+}
+
+/// Por que uma correção foi adiada.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deferral {
+    /// O alvo corrigido ficaria abaixo da dose mínima (ou de zero).
+    BelowMinimum,
+    /// O alvo corrigido passaria da dose máxima por sprint.
+    AboveMaximum,
+}
+
+/// Classificação da dosagem de um item frente à tolerância da linha.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToleranceStatus {
+    /// Ainda sem peso.
+    Pending,
+    InTolerance,
+    OutOfTolerance,
+    /// Não dosado.
+    Skipped,
 }
 
 #[allow(dead_code)]
 impl SprintItem {
-    pub fn new(item: Item, target: f64) -> Self {
-        SprintItem { item, target, actual: None }
+    pub fn new(item_id: String, target: Mass) -> Self {
+        SprintItem { item_id, target, actual: None, additions: Vec::new(), skipped: false, tolerance: Tolerance::default(), rounding: Mass::ZERO }
     }
 
-    pub fn set_actual(&mut self, actual: f64) {
-        self.actual = Some(actual);
+    pub fn tolerance_status(&self) -> ToleranceStatus {
+        match self.actual {
+            None if self.skipped => ToleranceStatus::Skipped,
+            None => ToleranceStatus::Pending,
+            Some(actual) if self.tolerance.accepts(self.target.kg(), actual.kg()) => ToleranceStatus::InTolerance,
+            Some(_) => ToleranceStatus::OutOfTolerance,
+        }
     }
 
-    pub fn divergence(&self) -> f64 {
+    /// Substitui as adições por uma única porção com o peso informado.
+    pub fn set_actual(&mut self, actual: Mass) {
+        self.additions = vec![Addition::new(actual, None, None)];
+        self.sync_actual();
+    }
+
+    pub fn add(&mut self, addition: Addition) {
+        self.additions.push(addition);
+        self.sync_actual();
+    }
+
+    pub fn remove(&mut self, addition_id: &str) -> bool {
+        let before = self.additions.len();
+        self.additions.retain(|a| a.id != addition_id);
+        self.sync_actual();
+        self.additions.len() != before
+    }
+
+    /// Marca o item como não dosado, descartando as porções lançadas.
+    pub fn skip(&mut self) {
+        self.additions.clear();
+        self.sync_actual();
+        self.skipped = true;
+    }
+
+    fn sync_actual(&mut self) {
+        self.actual = (!self.additions.is_empty()).then(|| self.additions.iter().map(|a| a.quantity).sum());
+        // pesar o item desfaz o "não dosado"
+        self.skipped = false;
+    }
+
+    pub fn divergence(&self) -> Mass {
         match self.actual {
             Some(a) => a - self.target,
-            None => 0.0,
+            None => Mass::ZERO,
         }
     }
 }
 
+/// Situação do sprint. Sprints em aberto (rascunho ou em pesagem) ficam na
+/// árvore `sprints`; ao serem concluídos ou abortados passam para o processo.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SprintStatus {
+    /// Criado, ainda sem nenhum peso informado.
+    Draft,
+    /// Pelo menos um item já foi pesado.
+    InProgress,
+    // sprints gravados antes dos rascunhos já estavam concluídos
+    #[default]
+    Completed,
+    /// Abandonado antes de ser concluído.
+    Aborted,
+    /// Concluído e depois anulado; o registro é mantido, mas não entra nos cálculos.
+    Voided,
+}
+
+impl SprintStatus {
+    /// Tabela de transições permitidas.
+    pub fn can_transition(self, to: SprintStatus) -> bool {
+        use SprintStatus::*;
+        matches!(
+            (self, to),
+            (Draft, InProgress)
+                | (Draft, Completed)
+                | (Draft, Aborted)
+                | (InProgress, Completed)
+                | (InProgress, Aborted)
+                | (Completed, Voided)
+        )
+    }
+
+    /// Rascunho ou em pesagem.
+    pub fn is_open(self) -> bool {
+        matches!(self, SprintStatus::Draft | SprintStatus::InProgress)
+    }
+}
+
+/// Liberação, por um supervisor, de um sprint com itens fora da tolerância.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToleranceOverride {
+    pub user_id: String,
+    pub username: String,
+    pub justification: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sprint {
     pub id: String,
     pub processo_id: String,
     pub numero: usize,
     pub itens: Vec<SprintItem>,
-    pub operador_id: User,
+    #[serde(deserialize_with = "deserialize_ref")]
+    pub operador_id: String,
     pub comentario: Option<String>,
+    #[serde(default)]
+    pub status: SprintStatus,
+    /// Motivo informado ao abortar ou anular.
+    #[serde(default)]
+    pub status_reason: Option<String>,
+    #[serde(default)]
+    pub status_changed_by: Option<String>,
+    #[serde(default)]
+    pub status_changed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tolerance_override: Option<ToleranceOverride>,
+    /// Reajuste proporcional em vigor, quando o processo mantém a proporção.
+    #[serde(default)]
+    pub ratio_adjustment: Option<RatioAdjustment>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 #[allow(dead_code)]
 impl Sprint {
-    pub fn new(processo_id: String, numero: usize, itens: Vec<SprintItem>, operador_id: User) -> Self {
+    pub fn new(processo_id: String, numero: usize, itens: Vec<SprintItem>, operador_id: String) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
         let now = Utc::now();
-        Sprint { id, processo_id, numero, itens, operador_id, comentario: None, created_at: now, updated_at: now }
+        Sprint {
+            id, processo_id, numero, itens, operador_id, comentario: None,
+            status: SprintStatus::Draft, status_reason: None, status_changed_by: None, status_changed_at: None,
+            tolerance_override: None, ratio_adjustment: None, created_at: now, updated_at: now,
+        }
     }
 
     pub fn add_item(&mut self, item: SprintItem) {
@@ -55,9 +239,9 @@ impl Sprint {
         self.touch();
     }
 
-    pub fn set_actual_for_item(&mut self, item_id: &str, actual: f64) -> bool {
+    pub fn set_actual_for_item(&mut self, item_id: &str, actual: Mass) -> bool {
         for it in &mut self.itens {
-            if it.item.id == item_id {
+            if it.item_id == item_id {
                 it.set_actual(actual);
                 self.touch();
                 return true;
@@ -66,63 +250,229 @@ impl Sprint {
         false
     }
 
-    pub fn total_divergence(&self) -> f64 {
+    fn item_mut(&mut self, item_id: &str) -> Result<&mut SprintItem, Box<dyn std::error::Error>> {
+        self.itens.iter_mut()
+            .find(|it| it.item_id == item_id)
+            .ok_or_else(|| "Item não pertence ao sprint".into())
+    }
+
+    pub fn total_divergence(&self) -> Mass {
         self.itens.iter().map(|i| i.divergence()).sum()
     }
 
-    pub fn apply_suggestions(&mut self, suggestions: &std::collections::HashMap<String, f64>) {
+    pub fn apply_suggestions(&mut self, suggestions: &std::collections::HashMap<String, Suggestion>) {
         for item in &mut self.itens {
-            if let Some(sugg) = suggestions.get(&item.item.id) {
-                item.target = *sugg;
+            if let Some(sugg) = suggestions.get(&item.item_id) {
+                item.target = sugg.target;
+                item.rounding = sugg.rounding;
             }
         }
         self.touch();
     }
 
-    pub fn divergence_per_item(&self) -> std::collections::HashMap<String, f64> {
+    pub fn divergence_per_item(&self) -> std::collections::HashMap<String, Mass> {
         let mut map = std::collections::HashMap::new();
         for it in &self.itens {
-            map.insert(it.item.id.clone(), it.divergence());
+            map.insert(it.item_id.clone(), it.divergence());
         }
         map
     }
 
     pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("sprints")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
-        Ok(())
+        index::save(db, self)
     }
 
     pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("sprints")?;
-        tree.remove(id.as_bytes())?;
+        index::delete::<Sprint>(db, id)
+    }
+
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<Sprint>, Box<dyn std::error::Error>> {
+        index::get(db, id)
+    }
+
+    pub fn get_all(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>) -> Result<Page<Sprint>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, None)
+    }
+
+    pub fn list_by_processo(processo_id: &str, db: &sled::Db) -> Result<Vec<Sprint>, Box<dyn std::error::Error>> {
+        index::load_by(db, "sprints_by_processo", processo_id)
+    }
+
+    /// Sprint em aberto do processo, se houver.
+    pub fn find_open(processo_id: &str, db: &sled::Db) -> Result<Option<Sprint>, Box<dyn std::error::Error>> {
+        Ok(Sprint::list_by_processo(processo_id, db)?
+            .into_iter()
+            .find(|s| s.is_open()))
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status.is_open()
+    }
+
+    /// Sprint concluído e não anulado, o único que conta nos cálculos do processo.
+    pub fn counts(&self) -> bool {
+        self.status == SprintStatus::Completed
+    }
+
+    /// Muda a situação do sprint, recusando transições fora da tabela.
+    pub fn transition(&mut self, to: SprintStatus) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.status.can_transition(to) {
+            return Err(format!("Transição de sprint inválida: {:?} → {:?}", self.status, to).into());
+        }
+        self.status = to;
+        self.touch();
         Ok(())
     }
 
-    pub fn get_all(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<Sprint>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("sprints")?;
-        let start = page * page_size;
-        let mut sprints = Vec::new();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_key, value) = result?;
-            let sprint: Sprint = serde_json::from_slice(&value)?;
-            sprints.push(sprint);
+    /// Grava o peso de um item de um sprint em aberto; o primeiro peso
+    /// tira o sprint do rascunho.
+    pub fn record_actual(&mut self, item_id: &str, actual: Mass) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_open()?;
+        self.item_mut(item_id)?.set_actual(actual);
+        self.weighed()
+    }
+
+    /// Acrescenta uma porção ao item de um sprint em aberto.
+    pub fn add_addition(&mut self, item_id: &str, addition: Addition) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_open()?;
+        self.item_mut(item_id)?.add(addition);
+        self.weighed()
+    }
+
+    /// Registra que o item não será dosado neste sprint.
+    pub fn skip_item(&mut self, item_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_open()?;
+        self.item_mut(item_id)?.skip();
+        self.weighed()
+    }
+
+    /// Desfaz uma porção lançada por engano.
+    pub fn remove_addition(&mut self, item_id: &str, addition_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_open()?;
+        if !self.item_mut(item_id)?.remove(addition_id) {
+            return Err("Adição não encontrada".into());
         }
-        Ok(sprints)
+        self.weighed()
     }
 
-    pub fn list_by_processo(processo_id: &str, db: &sled::Db) -> Result<Vec<Sprint>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("sprints")?;
-        let mut sprints = Vec::new();
-        for result in tree.iter() {
-            let (_, value) = result?;
-            let sprint: Sprint = serde_json::from_slice(&value)?;
-            if sprint.processo_id == processo_id {
-                sprints.push(sprint);
+    fn ensure_open(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_open() {
+            return Err("Sprint não está em aberto".into());
+        }
+        Ok(())
+    }
+
+    /// Os pesos mudaram: a liberação vale só para os pesos que o supervisor
+    /// viu, e o sprint sai do rascunho.
+    fn weighed(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.tolerance_override = None;
+        self.touch();
+        if self.status == SprintStatus::Draft {
+            self.transition(SprintStatus::InProgress)?;
+        }
+        Ok(())
+    }
+
+    /// Reajusta os alvos para manter a proporção da fórmula frente aos pesos
+    /// já informados. Itens já pesados abaixo do novo alvo precisam ser completados.
+    pub fn apply_ratio(&mut self, proportions: &[ItemProporcao], planned_mass: f64, capacity: Option<f64>) {
+        let actuals = self.itens.iter()
+            .filter_map(|it| it.actual.map(|a| (it.item_id.clone(), a.kg())))
+            .collect();
+        let (adjustment, targets) = compensation::ratio_targets(proportions, planned_mass, &actuals, capacity);
+        for it in &mut self.itens {
+            if let Some(target) = targets.get(&it.item_id) {
+                it.target = Mass::from_kg(*target);
             }
         }
-        Ok(sprints)
+        self.ratio_adjustment = (adjustment.extra_mass > 0.0).then_some(adjustment);
+        self.touch();
+    }
+
+    /// Itens pesados fora da tolerância.
+    pub fn out_of_tolerance(&self) -> Vec<&SprintItem> {
+        self.itens.iter()
+            .filter(|it| it.tolerance_status() == ToleranceStatus::OutOfTolerance)
+            .collect()
+    }
+
+    /// Libera a conclusão de um sprint em aberto com itens fora da tolerância.
+    pub fn override_tolerance(&mut self, supervisor: &User, justification: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_open() {
+            return Err("Sprint não está em aberto".into());
+        }
+        let justification = justification.trim();
+        if justification.is_empty() {
+            return Err("Informe a justificativa da liberação".into());
+        }
+        self.tolerance_override = Some(ToleranceOverride {
+            user_id: supervisor.id.clone(),
+            username: supervisor.username.clone(),
+            justification: justification.to_string(),
+            created_at: Utc::now(),
+        });
+        self.touch();
+        Ok(())
+    }
+
+    /// Abandona um sprint em aberto.
+    pub fn abort(&mut self, user_id: &str, reason: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        self.transition(SprintStatus::Aborted)?;
+        self.mark_change(user_id, reason.filter(|r| !r.trim().is_empty()));
+        Ok(())
+    }
+
+    /// Anula um sprint concluído. O motivo é obrigatório.
+    pub fn void(&mut self, user_id: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err("Informe o motivo da anulação".into());
+        }
+        self.transition(SprintStatus::Voided)?;
+        self.mark_change(user_id, Some(reason.to_string()));
+        Ok(())
+    }
+
+    fn mark_change(&mut self, user_id: &str, reason: Option<String>) {
+        self.status_reason = reason;
+        self.status_changed_by = Some(user_id.to_string());
+        self.status_changed_at = Some(Utc::now());
+    }
+
+    /// Conclui o sprint em aberto com os pesos gravados nele, item a item.
+    /// Itens ainda sem peso só passam com `skip_missing`, como não dosados.
+    /// Itens fora da tolerância exigem a liberação de um supervisor.
+    pub fn complete(&mut self, comentario: Option<String>, skip_missing: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let sem_peso = self.itens.iter().filter(|it| it.tolerance_status() == ToleranceStatus::Pending).count();
+        if sem_peso > 0 {
+            if !skip_missing {
+                return Err(format!("{} item(ns) sem peso; confirme que não foram dosados para concluir", sem_peso).into());
+            }
+            for it in self.itens.iter_mut().filter(|it| it.actual.is_none()) {
+                it.skip();
+            }
+        }
+        if comentario.is_some() {
+            self.comentario = comentario;
+        }
+        let fora = self.out_of_tolerance().len();
+        if fora > 0 && self.tolerance_override.is_none() {
+            return Err(format!("{} item(ns) fora da tolerância; a conclusão exige liberação de um supervisor", fora).into());
+        }
+        self.transition(SprintStatus::Completed)
+    }
+}
+
+impl Indexed for Sprint {
+    const TREE: &'static str = "sprints";
+    const INDEXES: &'static [&'static str] = &["sprints_by_processo"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        vec![IndexEntry::multi("sprints_by_processo", &self.processo_id, &self.id)]
     }
 }
 
@@ -134,4 +484,127 @@ impl crate::models::auditable::Auditable for Sprint {
     fn updated_at(&self) -> DateTime<Utc> {
         self.updated_at
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kg(v: f64) -> Mass {
+        Mass::from_kg(v)
+    }
+
+    #[test]
+    fn test_draft_is_persisted_and_completed() {
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let itens = vec![SprintItem::new("a".to_string(), kg(30.0)), SprintItem::new("b".to_string(), kg(20.0))];
+        let mut draft = Sprint::new("p1".to_string(), 1, itens, "op".to_string());
+        draft.save(&db).unwrap();
+
+        // peso gravado item a item, como na estação
+        draft.record_actual("a", kg(30.5)).unwrap();
+        assert_eq!(draft.status, SprintStatus::InProgress);
+        assert!(draft.record_actual("x", kg(1.0)).is_err());
+        draft.save(&db).unwrap();
+
+        let mut aberto = Sprint::find_open("p1", &db).unwrap().unwrap();
+        assert_eq!(aberto.id, draft.id);
+        assert_eq!(aberto.itens[0].actual, Some(kg(30.5)));
+        assert!(Sprint::find_open("p2", &db).unwrap().is_none());
+
+        // só conta o peso gravado no rascunho
+        assert!(aberto.complete(None, false).is_err());
+        aberto.record_actual("b", kg(19.8)).unwrap();
+        aberto.complete(Some("ok".to_string()), false).unwrap();
+        assert_eq!(aberto.status, SprintStatus::Completed);
+        assert_eq!(aberto.itens[0].actual, Some(kg(30.5)));
+        assert_eq!(aberto.itens[1].actual, Some(kg(19.8)));
+        assert_eq!(aberto.comentario.as_deref(), Some("ok"));
+
+        aberto.save(&db).unwrap();
+        assert!(Sprint::find_open("p1", &db).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transitions_are_enforced() {
+        let mut sprint = Sprint::new("p1".to_string(), 1, vec![SprintItem::new("a".to_string(), kg(10.0))], "op".to_string());
+        assert!(sprint.void("sup", "lote contaminado").is_err());
+
+        sprint.abort("op", Some("  ".to_string())).unwrap();
+        assert_eq!(sprint.status, SprintStatus::Aborted);
+        assert_eq!(sprint.status_reason, None);
+        assert!(sprint.record_actual("a", kg(10.0)).is_err());
+        assert!(sprint.transition(SprintStatus::Completed).is_err());
+
+        let mut sprint = Sprint::new("p1".to_string(), 2, vec![SprintItem::new("a".to_string(), kg(10.0))], "op".to_string());
+        // sem peso, só conclui confirmando que o item não foi dosado
+        assert!(sprint.complete(None, false).is_err());
+        sprint.complete(None, true).unwrap();
+        assert_eq!(sprint.itens[0].tolerance_status(), ToleranceStatus::Skipped);
+        assert!(sprint.abort("op", None).is_err());
+        assert!(sprint.void("sup", " ").is_err());
+        sprint.void("sup", "lote contaminado").unwrap();
+        assert_eq!(sprint.status, SprintStatus::Voided);
+        assert_eq!(sprint.status_reason.as_deref(), Some("lote contaminado"));
+        assert_eq!(sprint.status_changed_by.as_deref(), Some("sup"));
+        assert!(!sprint.counts());
+        assert!(sprint.transition(SprintStatus::Completed).is_err());
+    }
+
+    #[test]
+    fn test_out_of_tolerance_requires_override() {
+        let tolerancia = Tolerance { abs: Some(0.02), pct: None };
+        let itens = vec![
+            SprintItem { tolerance: tolerancia, ..SprintItem::new("aditivo".to_string(), kg(0.5)) },
+            SprintItem::new("farinha".to_string(), kg(20.0)),
+        ];
+        let mut sprint = Sprint::new("p1".to_string(), 1, itens, "op".to_string());
+        assert_eq!(sprint.itens[0].tolerance_status(), ToleranceStatus::Pending);
+
+        sprint.record_actual("aditivo", kg(0.55)).unwrap();
+        sprint.record_actual("farinha", kg(21.0)).unwrap();
+        assert_eq!(sprint.itens[0].tolerance_status(), ToleranceStatus::OutOfTolerance);
+        assert_eq!(sprint.itens[1].tolerance_status(), ToleranceStatus::InTolerance);
+
+        assert!(sprint.complete(None, false).is_err());
+        assert!(sprint.is_open());
+
+        let sup = User::new("sup".to_string(), "pw".to_string(), crate::models::user::Role::Supervisor).unwrap();
+        assert!(sprint.override_tolerance(&sup, "  ").is_err());
+        sprint.override_tolerance(&sup, "aditivo conferido pelo laboratório").unwrap();
+        // repesar descarta a liberação
+        sprint.record_actual("aditivo", kg(0.56)).unwrap();
+        assert!(sprint.complete(None, false).is_err());
+
+        sprint.override_tolerance(&sup, "aditivo conferido pelo laboratório").unwrap();
+        sprint.complete(None, false).unwrap();
+        assert_eq!(sprint.status, SprintStatus::Completed);
+        assert_eq!(sprint.tolerance_override.unwrap().username, "sup");
+    }
+
+    #[test]
+    fn test_actual_is_the_sum_of_additions() {
+        let mut sprint = Sprint::new("p1".to_string(), 1, vec![SprintItem::new("farinha".to_string(), kg(50.0))], "op".to_string());
+        sprint.add_addition("farinha", Addition::new(kg(25.0), Some("L-01".to_string()), Some("bal-1".to_string()))).unwrap();
+        assert_eq!(sprint.status, SprintStatus::InProgress);
+        sprint.add_addition("farinha", Addition::new(kg(25.0), Some(" ".to_string()), None)).unwrap();
+        sprint.add_addition("farinha", Addition::new(kg(0.35), None, None)).unwrap();
+        assert_eq!(sprint.itens[0].actual, Some(kg(50.35)));
+        assert_eq!(sprint.itens[0].additions[1].lot, None);
+        assert!(sprint.add_addition("sal", Addition::new(kg(1.0), None, None)).is_err());
+
+        // o complemento foi lançado por engano
+        let complemento = sprint.itens[0].additions[2].id.clone();
+        sprint.remove_addition("farinha", &complemento).unwrap();
+        assert_eq!(sprint.itens[0].actual, Some(kg(50.0)));
+        assert!(sprint.remove_addition("farinha", &complemento).is_err());
+
+        for id in sprint.itens[0].additions.iter().map(|a| a.id.clone()).collect::<Vec<_>>() {
+            sprint.remove_addition("farinha", &id).unwrap();
+        }
+        assert_eq!(sprint.itens[0].actual, None);
+
+        // registros antigos, sem adições, mantêm o peso gravado
+        let legado: SprintItem = crate::models::quantity::reading_legacy_kg(|| serde_json::from_str(r#"{"item_id":"a","target":1.0,"actual":1.2}"#)).unwrap();
+        assert_eq!((legado.actual, legado.additions.len()), (Some(kg(1.2)), 0));
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Serialize, Deserialize};
+
+/// Unidade em que um item é pesado ou medido. Os cálculos de fórmula,
+/// processo e sprint são sempre feitos em kg; a unidade só vale na entrada
+/// e na exibição das quantidades.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    #[default]
+    Kg,
+    G,
+    Mg,
+    L,
+    Ml,
+    /// Unidades contadas (sacos, cápsulas...).
+    Un,
+}
+
+impl Unit {
+    /// Quantos kg vale uma unidade. Volumes usam a densidade (kg/L) e itens
+    /// contados a massa por unidade (kg), informadas no item.
+    pub fn kg_factor(self, density: Option<f64>) -> Result<f64, String> {
+        let density = || match density {
+            Some(d) if d.is_finite() && d > 0.0 => Ok(d),
+            _ => Err(format!("Unidade {} exige densidade do item", self.symbol())),
+        };
+        Ok(match self {
+            Unit::Kg => 1.0,
+            Unit::G => 1e-3,
+            Unit::Mg => 1e-6,
+            Unit::L | Unit::Un => density()?,
+            Unit::Ml => density()? * 1e-3,
+        })
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Kg => "kg",
+            Unit::G => "g",
+            Unit::Mg => "mg",
+            Unit::L => "L",
+            Unit::Ml => "mL",
+            Unit::Un => "un",
+        }
+    }
+}
+
+/// Fator padrão para registros gravados antes das unidades (tudo em kg).
+pub(crate) fn kg_default_factor() -> f64 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kg_factor() {
+        assert_eq!(Unit::Kg.kg_factor(None).unwrap(), 1.0);
+        assert_eq!(Unit::G.kg_factor(None).unwrap(), 1e-3);
+        assert_eq!(Unit::Mg.kg_factor(Some(3.0)).unwrap(), 1e-6);
+        // óleo com 0,92 kg/L
+        assert!((Unit::L.kg_factor(Some(0.92)).unwrap() - 0.92).abs() < 1e-12);
+        assert!((Unit::Ml.kg_factor(Some(0.92)).unwrap() - 0.00092).abs() < 1e-12);
+        assert!(Unit::L.kg_factor(None).is_err());
+        assert!(Unit::Un.kg_factor(Some(0.0)).is_err());
+
+        assert_eq!(serde_json::to_value(Unit::Ml).unwrap(), serde_json::json!("ml"));
+    }
+}
@@ -1,16 +1,38 @@
 use serde::{Serialize, Deserialize};
 use uuid;
 use chrono::{DateTime, Utc};
+use argon2::Argon2;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 use crate::models::auditable::Auditable;
+use crate::models::index::{self, Indexed, IndexEntry};
+use crate::models::page::{Page, Sort, SortField};
+use crate::models::search;
 
 
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Role {
     Admin,
+    /// Aprova fórmulas e tolerâncias, mas não edita fórmulas.
+    Supervisor,
+    /// Controle de qualidade: somente leitura e relatórios.
+    Quality,
     User,
 }
 
+impl Role {
+    /// Converte o nome vindo do frontend; nomes desconhecidos são recusados.
+    pub fn from_name(name: &str) -> Result<Role, String> {
+        match name.to_lowercase().as_str() {
+            "admin" => Ok(Role::Admin),
+            "supervisor" => Ok(Role::Supervisor),
+            "quality" | "qualidade" => Ok(Role::Quality),
+            "user" | "usuario" | "usuário" => Ok(Role::User),
+            _ => Err(format!("Perfil desconhecido: {}", name)),
+        }
+    }
+}
+
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct User {
@@ -18,84 +40,144 @@ pub struct User {
     pub username: String,
     pub hashed_password: String,
     pub role: Role,
+    /// Obriga o usuário a trocar a senha no próximo login (ex.: admin semeado).
+    #[serde(default)]
+    pub must_change_password: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 
 
+/// Dados do usuário expostos ao frontend, sem o hash da senha.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserInfo {
+    pub id: String,
+    pub username: String,
+    pub role: Role,
+    pub must_change_password: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&User> for UserInfo {
+    fn from(user: &User) -> Self {
+        UserInfo {
+            id: user.id.clone(),
+            username: user.username.clone(),
+            role: user.role.clone(),
+            must_change_password: user.must_change_password,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+/// Gera o hash Argon2 (PHC string, com salt aleatório) de uma senha em texto puro.
+fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
 #[allow(dead_code)]
 impl User {
-    pub fn new(username: String, hashed_password: String, role: Role) -> Self {
+    /// Cria um usuário a partir da senha em texto puro; apenas o hash é armazenado.
+    pub fn new(username: String, password: String, role: Role) -> Result<Self, Box<dyn std::error::Error>> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = Utc::now();
-        User { id, username, hashed_password, role, created_at: now, updated_at: now }
+        let hashed_password = hash_password(&password)?;
+        Ok(User { id, username, hashed_password, role, must_change_password: false, created_at: now, updated_at: now })
     }
 
-    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("users")?;
-        let serialized = serde_json::to_vec(self)?;
-        tree.insert(self.id.as_bytes(), serialized)?;
+    /// Substitui a senha do usuário pelo hash da nova senha.
+    pub fn set_password(&mut self, password: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.hashed_password = hash_password(password)?;
+        self.touch();
         Ok(())
     }
 
-    pub fn update(&mut self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-       // atualização que atualiza usuário e sprints associados
+    /// Confere a senha informada contra o hash armazenado.
+    ///
+    /// Registros antigos guardavam a senha em texto puro; nesse caso a comparação
+    /// é direta e o chamador deve regravar a senha com `set_password`.
+    pub fn verify_password(&self, password: &str) -> bool {
+        match PasswordHash::new(&self.hashed_password) {
+            Ok(parsed) => Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok(),
+            Err(_) => self.hashed_password == password,
+        }
+    }
 
-        self.touch();
+    /// Indica se a senha ainda está gravada em texto puro (formato legado).
+    pub fn has_legacy_password(&self) -> bool {
+        PasswordHash::new(&self.hashed_password).is_err()
+    }
 
-        let users_tree = db.open_tree("users")?;
-        let sprints_tree = db.open_tree("sprints")?;
-
-        let serialized = serde_json::to_vec(self)?;
-        users_tree.insert(self.id.as_bytes(), serialized)?;
-
-        for result in sprints_tree.iter() {
-            let (key, value) = result?;
-            let mut sprint: crate::models::sprint::Sprint = serde_json::from_slice(&value)?;
-            if sprint.operador_id.id == self.id {
-                sprint.operador_id = self.clone();
-                sprint.touch();
-                let serialized_sprint = serde_json::to_vec(&sprint)?;
-                sprints_tree.insert(key, serialized_sprint)?;
-            }
-        }
+    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        index::save(db, self)
+    }
 
-        Ok(())
+    pub fn update(&mut self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        // sprints guardam só o id do operador, então não precisam ser regravados
+        self.touch();
+        self.save(db)
+    }
+
+    pub fn get_by_id(id: &str, db: &sled::Db) -> Result<Option<User>, Box<dyn std::error::Error>> {
+        index::get(db, id)
     }
 
     pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
-        let tree = db.open_tree("users")?;
-        tree.remove(id.as_bytes())?;
-        Ok(())
+        index::delete::<User>(db, id)
+    }
+
+    pub fn get_all(db: &sled::Db, page: usize, page_size: usize, cursor: Option<&str>, sort: Option<Sort>) -> Result<Page<User>, Box<dyn std::error::Error>> {
+        index::list(db, page, page_size, cursor, sort)
     }
 
-    pub fn get_all(db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<User>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("users")?;
-        let start = page * page_size;
-        let _end = start + page_size;
-        let mut users = Vec::new();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_key, value) = result?;
-            let user: User = serde_json::from_slice(&value)?;
-            users.push(user);
+    /// Quantos administradores estão cadastrados.
+    pub fn count_admins(db: &sled::Db) -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(index::lookup(db, "users_by_role", &format!("{:?}", Role::Admin))?.len())
+    }
+
+    pub fn find_by_username(username: &str, db: &sled::Db) -> Result<Option<User>, Box<dyn std::error::Error>> {
+        match index::lookup_unique(db, "users_by_username", username)? {
+            Some(id) => User::get_by_id(&id, db),
+            None => Ok(None),
         }
-        Ok(users)
-    }
-
-    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Vec<User>, Box<dyn std::error::Error>> {
-        let tree = db.open_tree("users")?;
-        let mut users = Vec::new();
-        let start = page * page_size;
-        let name_lower = name.to_lowercase();
-        for result in tree.iter().skip(start).take(page_size) {
-            let (_k, value) = result?;
-            let user: User = serde_json::from_slice(&value)?;
-            if user.username.to_lowercase().contains(&name_lower) {
-                users.push(user);
-            }
+    }
+
+    /// Busca pelo nome de usuário (`search::search`).
+    pub fn list_by_name(name: &str, db: &sled::Db, page: usize, page_size: usize) -> Result<Page<User>, Box<dyn std::error::Error>> {
+        search::search(db, name, |r: &User| &r.username, page, page_size)
+    }
+}
+
+impl Indexed for User {
+    const TREE: &'static str = "users";
+    const INDEXES: &'static [&'static str] = &["users_by_username", "users_by_name", "users_by_role", "users_by_created", "users_by_updated"];
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn index_entries(&self) -> Vec<IndexEntry> {
+        vec![
+            IndexEntry::unique("users_by_username", &self.username),
+            IndexEntry::by_name("users_by_name", &self.username, &self.id),
+            IndexEntry::multi("users_by_role", &format!("{:?}", self.role), &self.id),
+            IndexEntry::by_time("users_by_created", &self.created_at, &self.id),
+            IndexEntry::by_time("users_by_updated", &self.updated_at, &self.id),
+        ]
+    }
+
+    fn sort_index(field: SortField) -> Option<&'static str> {
+        match field {
+            SortField::Name => Some("users_by_name"),
+            SortField::CreatedAt => Some("users_by_created"),
+            SortField::UpdatedAt => Some("users_by_updated"),
+            SortField::Status => None,
         }
-        Ok(users)
     }
 }
 
@@ -0,0 +1,164 @@
+use std::io::{ErrorKind, Read, Write};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+use std::time::{Duration, Instant};
+use super::protocol::Frame;
+use super::stability::StabilityFilter;
+use super::{Health, ScaleConfig, ScaleEvent};
+
+/// Acima disso o buffer é lixo de uma linha com baud rate errado.
+const MAX_BUFFER: usize = 4096;
+
+/// Sem nenhum quadro por esse tempo a conexão é dada como perdida; pega o
+/// socket TCP meio aberto e o indicador desligado com o conversor ligado.
+const STALE_AFTER: Duration = Duration::from_secs(3);
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Canal de bytes até a balança: porta serial, socket TCP ou, nos testes,
+/// um pty.
+pub trait Link: Read + Write + Send {}
+impl<T: Read + Write + Send + ?Sized> Link for T {}
+
+/// Comandos do operador repassados à balança.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Tare,
+    Zero,
+    /// Reabre a conexão agora, sem esperar o intervalo de nova tentativa.
+    Reconnect,
+}
+
+/// Como terminou uma conexão.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exit {
+    /// O canal de comandos foi fechado: a balança foi removida.
+    Stopped,
+    /// O operador pediu reconexão.
+    Reconnect,
+    Lost(String),
+}
+
+/// Mantém a balança conectada: abre o canal com `open`, lê até a conexão
+/// cair e tenta de novo com espera crescente (0,5 s, 1 s, 2 s... até 30 s).
+/// A espera volta ao início depois de uma conexão que chegou a ler peso e
+/// quando o operador pede reconexão.
+pub fn supervise(
+    config: &ScaleConfig,
+    mut open: impl FnMut() -> Result<Box<dyn Link>, String>,
+    commands: Receiver<Command>,
+    mut emit: impl FnMut(ScaleEvent),
+) {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        emit(ScaleEvent::Health { scale_id: config.id.clone(), health: Health::Connecting });
+        let error = match open() {
+            Ok(link) => {
+                let mut online = false;
+                let exit = run(link, config, &commands, |event| {
+                    online |= matches!(event, ScaleEvent::Health { health: Health::Online, .. });
+                    emit(event)
+                });
+                match exit {
+                    Exit::Stopped => return,
+                    Exit::Reconnect => {
+                        backoff = INITIAL_BACKOFF;
+                        attempt = 0;
+                        continue;
+                    }
+                    Exit::Lost(error) => {
+                        if online {
+                            backoff = INITIAL_BACKOFF;
+                            attempt = 0;
+                        }
+                        error
+                    }
+                }
+            }
+            Err(error) => error,
+        };
+        attempt += 1;
+        emit(ScaleEvent::Health {
+            scale_id: config.id.clone(),
+            health: Health::Offline { error, attempt, retry_in_ms: backoff.as_millis() as u64 },
+        });
+        // espera a próxima tentativa atendendo a remoção e o pedido de reconexão
+        let deadline = Instant::now() + backoff;
+        backoff = loop {
+            match commands.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Err(RecvTimeoutError::Timeout) => break (backoff * 2).min(MAX_BACKOFF),
+                Ok(Command::Reconnect) => {
+                    attempt = 0;
+                    break INITIAL_BACKOFF;
+                }
+                // tara e zero sem conexão não têm efeito
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        };
+    }
+}
+
+/// Laço de leitura de uma conexão aberta. Pede o peso (se o protocolo
+/// exigir), repassa os comandos e publica cada quadro em `emit`.
+pub fn run(mut link: Box<dyn Link>, config: &ScaleConfig, commands: &Receiver<Command>, mut emit: impl FnMut(ScaleEvent)) -> Exit {
+    let protocol = config.protocol.driver();
+    let mut filter = StabilityFilter::new(config.stable_readings, config.stable_band);
+    let interval = Duration::from_millis(config.poll_interval_ms);
+    let mut last_poll: Option<Instant> = None;
+    let mut last_frame = Instant::now();
+    let mut online = false;
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 256];
+    let scale_id = config.id.clone();
+
+    loop {
+        let command = match commands.try_recv() {
+            Ok(Command::Tare) => Some(protocol.tare()),
+            Ok(Command::Zero) => Some(protocol.zero()),
+            Ok(Command::Reconnect) => return Exit::Reconnect,
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => return Exit::Stopped,
+        };
+        let poll = protocol.poll().filter(|_| last_poll.is_none_or(|t| t.elapsed() >= interval));
+        if poll.is_some() {
+            last_poll = Some(Instant::now());
+        }
+        for bytes in command.into_iter().chain(poll) {
+            if let Err(e) = link.write_all(bytes).and_then(|_| link.flush()) {
+                return Exit::Lost(e.to_string());
+            }
+        }
+
+        match link.read(&mut chunk) {
+            Ok(0) => return Exit::Lost("Conexão encerrada pela balança".to_string()),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted) => {}
+            Err(e) => return Exit::Lost(e.to_string()),
+        }
+
+        while let Some(frame) = protocol.next_frame(&mut buf) {
+            let Some(parsed) = protocol.parse(&frame) else { continue };
+            last_frame = Instant::now();
+            if !online {
+                online = true;
+                emit(ScaleEvent::Health { scale_id: scale_id.clone(), health: Health::Online });
+            }
+            emit(match parsed {
+                Frame::Weight(mut reading) => {
+                    reading.stable = filter.push(&reading);
+                    ScaleEvent::Reading { scale_id: scale_id.clone(), reading }
+                }
+                Frame::Ack => ScaleEvent::Acknowledged { scale_id: scale_id.clone() },
+                Frame::Error(message) => ScaleEvent::Error { scale_id: scale_id.clone(), message },
+            });
+        }
+        if buf.len() > MAX_BUFFER {
+            buf.clear();
+        }
+        if last_frame.elapsed() > STALE_AFTER {
+            return Exit::Lost("Sem dados da balança".to_string());
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Leitura de balanças seriais e de indicadores em rede. Cada balança
+//! configurada tem uma thread que mantém a conexão, fala o protocolo dela e
+//! publica leituras e situação como eventos do Tauri; o frontend mostra o
+//! peso ao vivo e o sprint lança o último peso estável.
+
+pub mod driver;
+pub mod mtsics;
+pub mod protocol;
+pub mod simulator;
+pub mod stability;
+pub mod toledo;
+pub mod transport;
+
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::quantity::Mass;
+use driver::{Command, Link};
+use protocol::{ProtocolKind, Reading};
+use simulator::Simulator;
+use transport::Transport;
+
+/// Nome do evento do Tauri com as leituras e a situação das balanças.
+pub const SCALE_EVENT: &str = "scale-event";
+
+/// Balança ligada ao simulador de treinamento; não é gravada na estação.
+pub const SIMULATOR_ID: &str = "simulador";
+
+/// Leitura mais antiga que isso não serve para lançar peso.
+const MAX_READING_AGE: Duration = Duration::from_secs(2);
+
+/// Configuração de uma balança. As configurações ficam no banco local, por
+/// isso cada estação tem as suas balanças.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScaleConfig {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub transport: Transport,
+    pub protocol: ProtocolKind,
+    /// Leituras estáveis seguidas exigidas para confirmar o peso.
+    #[serde(default = "default_stable_readings")]
+    pub stable_readings: usize,
+    /// Variação aceita entre essas leituras (`Mass`, em mg inteiros).
+    #[serde(default)]
+    pub stable_band: Mass,
+    /// Intervalo entre pedidos de peso nos protocolos de pergunta e resposta.
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_stable_readings() -> usize {
+    3
+}
+
+fn default_poll_interval_ms() -> u64 {
+    200
+}
+
+impl ScaleConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Informe a identificação da balança".to_string());
+        }
+        if self.stable_readings == 0 || self.poll_interval_ms == 0 {
+            return Err("Configuração de balança inválida".to_string());
+        }
+        if self.stable_band < Mass::ZERO {
+            return Err("Faixa de estabilidade inválida".to_string());
+        }
+        self.transport.validate()
+    }
+
+    pub fn save(&self, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        let tree = db.open_tree("scale_configs")?;
+        tree.insert(self.id.as_bytes(), serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    pub fn delete(id: &str, db: &sled::Db) -> Result<(), Box<dyn std::error::Error>> {
+        let tree = db.open_tree("scale_configs")?;
+        tree.remove(id.as_bytes())?;
+        Ok(())
+    }
+
+    /// Balanças configuradas nesta estação.
+    pub fn all(db: &sled::Db) -> Result<Vec<ScaleConfig>, Box<dyn std::error::Error>> {
+        let tree = db.open_tree("scale_configs")?;
+        let mut configs = Vec::new();
+        for result in tree.iter() {
+            let (_k, value) = result?;
+            configs.push(serde_json::from_slice(&value)?);
+        }
+        Ok(configs)
+    }
+}
+
+/// Situação da conexão com a balança.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Health {
+    /// Abrindo a conexão ou esperando o primeiro quadro.
+    #[default]
+    Connecting,
+    Online,
+    /// Conexão perdida; nova tentativa em `retry_in_ms`.
+    Offline { error: String, attempt: u32, retry_in_ms: u64 },
+}
+
+/// Evento publicado para o frontend em [`SCALE_EVENT`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScaleEvent {
+    /// Peso lido; `stable` já passou pelo filtro de estabilidade.
+    Reading { scale_id: String, reading: Reading },
+    /// Tara ou zero confirmados pela balança. Só o MT-SICS confirma; na
+    /// Toledo o efeito aparece apenas na leitura seguinte.
+    Acknowledged { scale_id: String },
+    Error { scale_id: String, message: String },
+    Health { scale_id: String, health: Health },
+}
+
+/// Situação de uma balança para a tela.
+#[derive(Serialize, Debug, Clone)]
+pub struct ScaleStatus {
+    pub config: ScaleConfig,
+    pub health: Health,
+    pub reading: Option<Reading>,
+    /// Há quanto tempo chegou a última leitura.
+    pub reading_age_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Estado compartilhado com a thread da balança.
+#[derive(Default)]
+struct Live {
+    health: Health,
+    reading: Option<(Reading, Instant)>,
+    error: Option<String>,
+}
+
+impl Live {
+    fn update(&mut self, event: &ScaleEvent) {
+        match event {
+            ScaleEvent::Reading { reading, .. } => {
+                self.reading = Some((*reading, Instant::now()));
+                self.error = None;
+            }
+            ScaleEvent::Acknowledged { .. } => self.error = None,
+            ScaleEvent::Error { message, .. } => self.error = Some(message.clone()),
+            ScaleEvent::Health { health, .. } => {
+                if let Health::Offline { error, .. } = health {
+                    self.error = Some(error.clone());
+                }
+                self.health = health.clone();
+            }
+        }
+    }
+}
+
+struct Connection {
+    config: ScaleConfig,
+    commands: Sender<Command>,
+    live: Arc<Mutex<Live>>,
+}
+
+/// Balanças em uso nesta estação e o simulador de treinamento, guardados
+/// como estado do Tauri.
+#[derive(Default)]
+pub struct ScaleManager {
+    scales: Mutex<HashMap<String, Connection>>,
+    simulator: Mutex<Option<Simulator>>,
+}
+
+impl ScaleManager {
+    /// Passa a manter a balança conectada, tentando de novo enquanto ela
+    /// estiver fora. Conectar de novo a mesma balança substitui a conexão.
+    pub fn connect(&self, config: ScaleConfig, emit: impl Fn(ScaleEvent) + Send + 'static) -> Result<(), String> {
+        config.validate()?;
+        let transport = config.transport.clone();
+        self.attach(config, move || transport.open(), emit);
+        Ok(())
+    }
+
+    /// Conecta as balanças configuradas na estação. Uma configuração com
+    /// problema fica de fora sem impedir as outras nem a abertura do app.
+    pub fn connect_all(&self, db: &sled::Db, emit: impl Fn(ScaleEvent) + Clone + Send + 'static) {
+        let configs = match ScaleConfig::all(db) {
+            Ok(configs) => configs,
+            Err(e) => {
+                eprintln!("failed to load scale configs: {}", e);
+                return;
+            }
+        };
+        for config in configs {
+            let id = config.id.clone();
+            if let Err(e) = self.connect(config, emit.clone()) {
+                eprintln!("failed to connect scale {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Mantém a balança conectada abrindo o canal com `open`.
+    pub fn attach(
+        &self,
+        config: ScaleConfig,
+        open: impl FnMut() -> Result<Box<dyn Link>, String> + Send + 'static,
+        emit: impl Fn(ScaleEvent) + Send + 'static,
+    ) {
+        let (commands, receiver) = mpsc::channel();
+        let live = Arc::new(Mutex::new(Live::default()));
+        let shared = live.clone();
+        let thread_config = config.clone();
+        thread::spawn(move || {
+            driver::supervise(&thread_config, open, receiver, |event| {
+                shared.lock().unwrap().update(&event);
+                emit(event);
+            })
+        });
+        // a conexão anterior para quando seu canal de comandos é fechado
+        self.scales.lock().unwrap().insert(config.id.clone(), Connection { config, commands, live });
+    }
+
+    pub fn disconnect(&self, scale_id: &str) -> Result<(), String> {
+        self.scales.lock().unwrap().remove(scale_id).map(|_| ()).ok_or("Balança não conectada".to_string())
+    }
+
+    pub fn send(&self, scale_id: &str, command: Command) -> Result<(), String> {
+        let scales = self.scales.lock().unwrap();
+        let connection = scales.get(scale_id).ok_or("Balança não conectada".to_string())?;
+        if command != Command::Reconnect && connection.live.lock().unwrap().health != Health::Online {
+            return Err("Balança fora de linha".to_string());
+        }
+        connection.commands.send(command).map_err(|_| "Balança desconectada".to_string())
+    }
+
+    /// Último peso estável e recente da balança, para lançar no sprint.
+    pub fn stable_weight(&self, scale_id: &str) -> Result<Mass, String> {
+        let scales = self.scales.lock().unwrap();
+        let connection = scales.get(scale_id).ok_or("Balança não conectada".to_string())?;
+        let live = connection.live.lock().unwrap();
+        if live.health != Health::Online {
+            return Err("Balança fora de linha".to_string());
+        }
+        match live.reading {
+            Some((reading, at)) if at.elapsed() <= MAX_READING_AGE => {
+                if reading.stable {
+                    Ok(reading.weight)
+                } else {
+                    Err("O peso ainda não estabilizou".to_string())
+                }
+            }
+            _ => Err("Sem leitura recente da balança".to_string()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<ScaleStatus> {
+        let scales = self.scales.lock().unwrap();
+        let mut list: Vec<ScaleStatus> = scales.values().map(|c| {
+            let live = c.live.lock().unwrap();
+            ScaleStatus {
+                config: c.config.clone(),
+                health: live.health.clone(),
+                reading: live.reading.map(|(r, _)| r),
+                reading_age_ms: live.reading.map(|(_, at)| at.elapsed().as_millis() as u64),
+                error: live.error.clone(),
+            }
+        }).collect();
+        list.sort_by(|a, b| a.config.id.cmp(&b.config.id));
+        list
+    }
+
+    /// Liga o simulador em uma porta TCP local (0 escolhe uma livre) e
+    /// conecta a balança de treinamento a ele.
+    pub fn start_simulator(&self, protocol: ProtocolKind, port: u16, emit: impl Fn(ScaleEvent) + Send + 'static) -> Result<ScaleConfig, String> {
+        let simulator = Simulator::new(protocol);
+        let addr = simulator.listen(port)?;
+        *self.simulator.lock().unwrap() = Some(simulator);
+        let config = ScaleConfig {
+            id: SIMULATOR_ID.to_string(),
+            name: "Simulador".to_string(),
+            transport: Transport::Tcp { host: Ipv4Addr::LOCALHOST.to_string(), port: addr.port() },
+            protocol,
+            stable_readings: default_stable_readings(),
+            stable_band: Mass::ZERO,
+            poll_interval_ms: default_poll_interval_ms(),
+        };
+        self.connect(config.clone(), emit)?;
+        Ok(config)
+    }
+
+    /// Coloca um peso no prato do simulador.
+    pub fn set_simulator_load(&self, load: Mass, stable: bool) -> Result<(), String> {
+        match self.simulator.lock().unwrap().as_ref() {
+            Some(simulator) => {
+                simulator.set_load(load, stable);
+                Ok(())
+            }
+            None => Err("Simulador desligado".to_string()),
+        }
+    }
+
+    pub fn stop_simulator(&self) {
+        self.scales.lock().unwrap().remove(SIMULATOR_ID);
+        self.simulator.lock().unwrap().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(protocol: ProtocolKind, transport: Transport) -> ScaleConfig {
+        ScaleConfig {
+            id: "b1".to_string(),
+            name: "Balança 1".to_string(),
+            transport,
+            protocol,
+            stable_readings: 2,
+            stable_band: Mass::ZERO,
+            poll_interval_ms: 20,
+        }
+    }
+
+    /// Espera até a condição valer, por no máximo 5 s.
+    fn wait_for(mut cond: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !cond() {
+            assert!(start.elapsed() < Duration::from_secs(5), "tempo esgotado");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_manual_reconnect_resets_backoff() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (events, received) = std::sync::mpsc::channel();
+        let config = config(ProtocolKind::Toledo, Transport::Tcp { host: "127.0.0.1".to_string(), port: 0 });
+        let supervisor = thread::spawn(move || {
+            driver::supervise(&config, || Err("sem balança".to_string()), rx, |event| {
+                if let ScaleEvent::Health { health: Health::Offline { attempt, retry_in_ms, .. }, .. } = event {
+                    events.send((attempt, retry_in_ms)).unwrap();
+                }
+            })
+        });
+
+        assert_eq!(received.recv().unwrap(), (1, 500));
+        assert_eq!(received.recv().unwrap(), (2, 1000));
+        // o pedido do operador recomeça a espera do início
+        tx.send(Command::Reconnect).unwrap();
+        assert_eq!(received.recv().unwrap(), (1, 500));
+
+        drop(tx);
+        supervisor.join().unwrap();
+    }
+
+    /// Pares de pseudoterminal só existem em sistemas unix.
+    #[cfg(unix)]
+    mod pty {
+        use super::*;
+        use serialport::{SerialPort, TTYPort};
+        use transport::READ_TIMEOUT;
+
+        fn through_pty(protocol: ProtocolKind) {
+            let (mut master, mut slave) = TTYPort::pair().unwrap();
+            master.set_timeout(READ_TIMEOUT).unwrap();
+            slave.set_timeout(READ_TIMEOUT).unwrap();
+            let simulator = Simulator::spawn(Box::new(slave), protocol);
+            let manager = ScaleManager::default();
+            let events = Arc::new(Mutex::new(Vec::new()));
+            let sink = events.clone();
+            let serial = Transport::Serial { port: "pty".to_string(), baud_rate: 9600 };
+            let mut pty = Some(master);
+            manager.attach(
+                config(protocol, serial),
+                move || pty.take().map(|p| Box::new(p) as Box<dyn Link>).ok_or("pty fechado".to_string()),
+                move |e| sink.lock().unwrap().push(e),
+            );
+
+            // em movimento não lança
+            simulator.set_load(Mass::from_kg(12.5), false);
+            wait_for(|| manager.list()[0].reading.is_some_and(|r| r.weight == Mass::from_kg(12.5)));
+            assert_eq!(manager.list()[0].health, Health::Online);
+            assert!(manager.stable_weight("b1").is_err());
+
+            simulator.set_load(Mass::from_kg(12.5), true);
+            wait_for(|| manager.stable_weight("b1").is_ok());
+            assert_eq!(manager.stable_weight("b1").unwrap(), Mass::from_kg(12.5));
+
+            // tara com o recipiente: o indicado passa a ser o líquido
+            manager.send("b1", Command::Tare).unwrap();
+            wait_for(|| manager.stable_weight("b1") == Ok(Mass::ZERO));
+            simulator.set_load(Mass::from_kg(15.0), true);
+            wait_for(|| manager.stable_weight("b1") == Ok(Mass::from_kg(2.5)));
+
+            assert!(events.lock().unwrap().iter().any(|e| matches!(e, ScaleEvent::Reading { scale_id, reading } if scale_id == "b1" && reading.stable)));
+            manager.disconnect("b1").unwrap();
+            assert!(manager.list().is_empty());
+            assert!(manager.send("b1", Command::Zero).is_err());
+        }
+
+        #[test]
+        fn test_mtsics_scale_through_pty() {
+            through_pty(ProtocolKind::MtSics);
+        }
+
+        #[test]
+        fn test_toledo_scale_through_pty() {
+            through_pty(ProtocolKind::Toledo);
+        }
+    }
+
+    #[test]
+    fn test_tcp_scale_reconnects() {
+        let simulator = Simulator::new(ProtocolKind::Toledo);
+        let port = simulator.listen(0).unwrap().port();
+        simulator.set_load(Mass::from_kg(3.0), true);
+
+        let db = sled::Config::default().temporary(true).open().unwrap();
+        let tcp = Transport::Tcp { host: "127.0.0.1".to_string(), port };
+        config(ProtocolKind::Toledo, tcp).save(&db).unwrap();
+        // configuração sem endereço fica de fora sem barrar as outras
+        let sem_host = Transport::Tcp { host: String::new(), port };
+        ScaleConfig { id: "b0".to_string(), ..config(ProtocolKind::Toledo, sem_host) }.save(&db).unwrap();
+        assert_eq!(ScaleConfig::all(&db).unwrap().len(), 2);
+
+        let manager = ScaleManager::default();
+        manager.connect_all(&db, |_| {});
+        assert_eq!(manager.list().len(), 1);
+        wait_for(|| manager.stable_weight("b1") == Ok(Mass::from_kg(3.0)));
+
+        // indicador desligado: fica fora de linha e tenta de novo
+        simulator.set_off(true);
+        wait_for(|| matches!(manager.list()[0].health, Health::Offline { .. }));
+        assert!(manager.stable_weight("b1").is_err());
+
+        // religado na mesma porta, que nunca foi liberada
+        simulator.set_load(Mass::from_kg(4.0), true);
+        simulator.set_off(false);
+        manager.send("b1", Command::Reconnect).unwrap();
+        wait_for(|| manager.stable_weight("b1") == Ok(Mass::from_kg(4.0)));
+
+        ScaleConfig::delete("b0", &db).unwrap();
+        ScaleConfig::delete("b1", &db).unwrap();
+        assert!(ScaleConfig::all(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_training_simulator() {
+        let manager = ScaleManager::default();
+        assert!(manager.set_simulator_load(Mass::from_kg(1.0), true).is_err());
+        manager.start_simulator(ProtocolKind::MtSics, 0, |_| {}).unwrap();
+        manager.set_simulator_load(Mass::from_kg(7.25), true).unwrap();
+        wait_for(|| manager.stable_weight(SIMULATOR_ID) == Ok(Mass::from_kg(7.25)));
+        manager.stop_simulator();
+        assert!(manager.list().is_empty());
+    }
+}
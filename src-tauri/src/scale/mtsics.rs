@@ -0,0 +1,68 @@
+use super::protocol::{mass_in, Frame, Protocol, Reading};
+
+/// MT-SICS nível 0: o peso é pedido com `SI` e chega como
+/// `S S      12.345 kg` (estável) ou `S D ...` (em movimento).
+pub struct MtSics;
+
+impl Protocol for MtSics {
+    fn poll(&self) -> Option<&'static [u8]> {
+        Some(b"SI\r\n")
+    }
+
+    fn tare(&self) -> &'static [u8] {
+        b"T\r\n"
+    }
+
+    fn zero(&self) -> &'static [u8] {
+        b"Z\r\n"
+    }
+
+    fn parse(&self, frame: &[u8]) -> Option<Frame> {
+        let text = std::str::from_utf8(frame).ok()?;
+        let mut parts = text.split_whitespace();
+        let id = parts.next()?;
+        let status = parts.next().unwrap_or("");
+        match (id, status) {
+            ("S", "S" | "D") => {
+                let value = parts.next()?.parse::<f64>().ok()?;
+                let weight = mass_in(value, parts.next()?)?;
+                Some(Frame::Weight(Reading { weight, tare: None, stable: status == "S" }))
+            }
+            ("S" | "T" | "Z", "+") => Some(Frame::Error("Balança acima da capacidade".to_string())),
+            ("S" | "T" | "Z", "-") => Some(Frame::Error("Balança abaixo do zero".to_string())),
+            ("T" | "Z", "I") => Some(Frame::Error("Balança não executou o comando".to_string())),
+            ("T", "S") | ("Z", "A") => Some(Frame::Ack),
+            ("ES" | "ET" | "EL", _) => Some(Frame::Error(format!("Erro de comunicação MT-SICS ({})", id))),
+            // "S I": ocupada, a próxima consulta responde
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quantity::Mass;
+
+    #[test]
+    fn test_parse_mtsics() {
+        let p = MtSics;
+        assert_eq!(
+            p.parse(b"S S      12.345 kg"),
+            Some(Frame::Weight(Reading { weight: Mass::from_kg(12.345), tare: None, stable: true }))
+        );
+        assert_eq!(
+            p.parse(b"S D    -250.0 g"),
+            Some(Frame::Weight(Reading { weight: Mass::from_kg(-0.25), tare: None, stable: false }))
+        );
+        assert_eq!(p.parse(b"Z A"), Some(Frame::Ack));
+        assert!(matches!(p.parse(b"S +"), Some(Frame::Error(_))));
+        assert_eq!(p.parse(b"S I"), None);
+
+        let mut buf = b"S S 1.000 kg\r\nZ A\r\nS D".to_vec();
+        assert_eq!(p.next_frame(&mut buf).unwrap(), b"S S 1.000 kg");
+        assert_eq!(p.next_frame(&mut buf).unwrap(), b"Z A");
+        assert_eq!(p.next_frame(&mut buf), None);
+        assert_eq!(buf, b"S D");
+    }
+}
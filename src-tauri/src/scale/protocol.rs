@@ -0,0 +1,82 @@
+use serde::{Serialize, Deserialize};
+use crate::models::quantity::Mass;
+use super::{mtsics::MtSics, toledo::Toledo};
+
+/// Peso lido da balança. `weight` é o peso indicado (líquido quando há
+/// tara), como `Mass`: mg inteiros, qualquer que seja a unidade da balança.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub weight: Mass,
+    #[serde(default)]
+    pub tare: Option<Mass>,
+    /// Estabilidade informada pela balança; depois do filtro, a confirmada.
+    pub stable: bool,
+}
+
+/// Significado de um quadro recebido.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Weight(Reading),
+    /// Tara ou zero executados (protocolos que confirmam comandos).
+    Ack,
+    /// Comando recusado ou peso fora da faixa.
+    Error(String),
+}
+
+/// Formato dos quadros de uma família de balanças.
+pub trait Protocol: Send {
+    /// Comando que pede um peso; `None` quando a balança transmite sozinha.
+    fn poll(&self) -> Option<&'static [u8]>;
+
+    fn tare(&self) -> &'static [u8];
+
+    fn zero(&self) -> &'static [u8];
+
+    /// Tira do buffer o próximo quadro completo. O padrão são linhas
+    /// terminadas em CR ou CRLF.
+    fn next_frame(&self, buf: &mut Vec<u8>) -> Option<Vec<u8>> {
+        let end = buf.iter().position(|&b| b == b'\r' || b == b'\n')?;
+        let frame: Vec<u8> = buf.drain(..=end).collect();
+        let frame = &frame[..frame.len() - 1];
+        if frame.is_empty() {
+            // LF do CRLF anterior
+            return self.next_frame(buf);
+        }
+        Some(frame.to_vec())
+    }
+
+    /// Interpreta um quadro; `None` para quadros que não interessam.
+    fn parse(&self, frame: &[u8]) -> Option<Frame>;
+}
+
+/// Protocolos suportados, escolhidos na configuração da balança.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtocolKind {
+    /// Mettler Toledo MT-SICS: pergunta e resposta em linhas de texto.
+    MtSics,
+    /// Saída contínua Toledo (8142 e compatíveis).
+    Toledo,
+}
+
+impl ProtocolKind {
+    pub fn driver(self) -> Box<dyn Protocol> {
+        match self {
+            ProtocolKind::MtSics => Box::new(MtSics),
+            ProtocolKind::Toledo => Box::new(Toledo),
+        }
+    }
+}
+
+/// Converte o valor lido para `Mass` conforme a unidade transmitida.
+pub(crate) fn mass_in(value: f64, unit: &str) -> Option<Mass> {
+    let factor = match unit {
+        "kg" => 1.0,
+        "g" => 1e-3,
+        "mg" => 1e-6,
+        "t" => 1e3,
+        "lb" => 0.453_592_37,
+        _ => return None,
+    };
+    value.is_finite().then(|| Mass::from_kg(value * factor))
+}
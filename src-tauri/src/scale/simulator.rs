@@ -0,0 +1,223 @@
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use crate::models::quantity::Mass;
+use super::driver::Link;
+use super::mtsics::MtSics;
+use super::protocol::{Protocol, ProtocolKind};
+use super::transport::READ_TIMEOUT;
+
+/// Intervalo entre quadros da saída contínua.
+const FRAME_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Default)]
+struct State {
+    /// Massa sobre o prato.
+    load: Mass,
+    stable: bool,
+    zero: Mass,
+    tare: Mass,
+    /// Indicador desligado: a porta continua aberta, mas as conexões caem.
+    off: bool,
+}
+
+impl State {
+    fn net(&self) -> Mass {
+        self.load - self.zero - self.tare
+    }
+}
+
+/// Balança simulada, falando MT-SICS ou a saída contínua Toledo, para os
+/// testes e para treinar operadores sem balança. Atende um canal já aberto
+/// (`spawn`) ou conexões TCP locais (`listen`), todos vendo o mesmo prato.
+/// Para quando é descartada.
+pub struct Simulator {
+    protocol: ProtocolKind,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Simulator {
+    pub fn new(protocol: ProtocolKind) -> Simulator {
+        Simulator {
+            protocol,
+            state: Arc::new(Mutex::new(State { stable: true, ..State::default() })),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Simula a balança do outro lado de `link`.
+    #[cfg(all(test, unix))]
+    pub fn spawn(link: Box<dyn Link>, protocol: ProtocolKind) -> Simulator {
+        let simulator = Simulator::new(protocol);
+        serve(protocol, simulator.state.clone(), simulator.stop.clone(), link);
+        simulator
+    }
+
+    /// Aceita conexões em 127.0.0.1 (porta 0 escolhe uma livre) e devolve o
+    /// endereço em que está ouvindo.
+    pub fn listen(&self, port: u16) -> Result<SocketAddr, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).map_err(|e| format!("Não foi possível abrir a porta {}: {}", port, e))?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+        // aceite sem bloqueio, para parar junto com o simulador
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let (protocol, state, stop) = (self.protocol, self.state.clone(), self.stop.clone());
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok(_) if state.lock().unwrap().off => continue,
+                    Ok((stream, _)) => {
+                        let ready = stream.set_nonblocking(false)
+                            .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)));
+                        if ready.is_ok() {
+                            serve(protocol, state.clone(), stop.clone(), Box::new(stream));
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(READ_TIMEOUT),
+                    Err(_) => return,
+                }
+            }
+        });
+        Ok(addr)
+    }
+
+    /// Coloca `load` sobre o prato, estável ou em movimento.
+    pub fn set_load(&self, load: Mass, stable: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.load = load;
+        state.stable = stable;
+    }
+
+    /// Desliga ou religa o indicador, como um conversor TCP que continua
+    /// ouvindo e derruba as conexões enquanto a balança está desligada.
+    #[cfg(test)]
+    pub fn set_off(&self, off: bool) {
+        self.state.lock().unwrap().off = off;
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Atende uma conexão até o simulador parar ou o outro lado fechar.
+fn serve(protocol: ProtocolKind, state: Arc<Mutex<State>>, stop: Arc<AtomicBool>, mut link: Box<dyn Link>) {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 64];
+        while !stop.load(Ordering::Relaxed) && !state.lock().unwrap().off {
+            if protocol == ProtocolKind::Toledo {
+                let frame = toledo_frame(&state.lock().unwrap());
+                if link.write_all(&frame).is_err() {
+                    return;
+                }
+                thread::sleep(FRAME_INTERVAL);
+            }
+            match link.read(&mut chunk) {
+                Ok(0) => return,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => continue,
+                Err(_) => return,
+            }
+            let replies = match protocol {
+                ProtocolKind::MtSics => mtsics_replies(&mut state.lock().unwrap(), &mut buf),
+                ProtocolKind::Toledo => {
+                    toledo_commands(&mut state.lock().unwrap(), &buf);
+                    buf.clear();
+                    Vec::new()
+                }
+            };
+            for reply in replies {
+                if link.write_all(reply.as_bytes()).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+fn mtsics_replies(state: &mut State, buf: &mut Vec<u8>) -> Vec<String> {
+    let mut replies = Vec::new();
+    while let Some(line) = MtSics.next_frame(buf) {
+        let reply = match line.as_slice() {
+            b"SI" | b"S" => format!("S {} {:>10.3} kg\r\n", if state.stable { "S" } else { "D" }, state.net().kg()),
+            _ if !state.stable => format!("{} I\r\n", String::from_utf8_lossy(&line)),
+            b"T" => {
+                state.tare = state.load - state.zero;
+                format!("T S {:>10.3} kg\r\n", state.tare.kg())
+            }
+            b"Z" => {
+                state.zero = state.load;
+                state.tare = Mass::ZERO;
+                "Z A\r\n".to_string()
+            }
+            _ => "ES\r\n".to_string(),
+        };
+        replies.push(reply);
+    }
+    replies
+}
+
+fn toledo_commands(state: &mut State, input: &[u8]) {
+    for byte in input {
+        match byte {
+            b'T' => state.tare = state.load - state.zero,
+            b'Z' => {
+                state.zero = state.load;
+                state.tare = Mass::ZERO;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Quadro Toledo com três casas decimais, em kg. Os campos têm 6 dígitos;
+/// de 1000 kg para cima o quadro sai com o bit de sobrecarga.
+fn toledo_frame(state: &State) -> Vec<u8> {
+    const FIELD_MAX: i64 = 999_999;
+    let net = state.net();
+    let raw = |m: Mass| (m.kg().abs() * 1000.0).round() as i64;
+    let digits = |m: Mass| format!("{:06}", raw(m).min(FIELD_MAX));
+    let mut swb = 0x20 | 0x10;
+    if state.tare != Mass::ZERO {
+        swb |= 0x01;
+    }
+    if net < Mass::ZERO {
+        swb |= 0x02;
+    }
+    if raw(net) > FIELD_MAX || raw(state.tare) > FIELD_MAX {
+        swb |= 0x04;
+    }
+    if !state.stable {
+        swb |= 0x08;
+    }
+    let mut frame = vec![0x02, 0x20 | 5, swb, 0x20];
+    frame.extend_from_slice(digits(net).as_bytes());
+    frame.extend_from_slice(digits(state.tare).as_bytes());
+    frame.push(b'\r');
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scale::protocol::Frame;
+    use crate::scale::toledo::Toledo;
+
+    #[test]
+    fn test_toledo_frame_overrange() {
+        let state = |kg: f64| State { load: Mass::from_kg(kg), stable: true, ..State::default() };
+        let frame = toledo_frame(&state(999.999));
+        assert!(matches!(Toledo.parse(&frame), Some(Frame::Weight(r)) if r.weight == Mass::from_kg(999.999)));
+
+        // acima da faixa o quadro mantém o tamanho e acusa sobrecarga
+        let frame = toledo_frame(&state(1500.0));
+        assert_eq!(frame.len(), toledo_frame(&state(1.0)).len());
+        assert!(matches!(Toledo.parse(&frame), Some(Frame::Error(_))));
+    }
+}
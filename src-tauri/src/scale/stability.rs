@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+use crate::models::quantity::Mass;
+use super::protocol::Reading;
+
+/// Confirma a estabilidade em software: o peso só é estável depois de
+/// `window` leituras seguidas que a balança marcou como estáveis e que não
+/// variam mais que `band` entre si. Evita capturar o pico de uma balança
+/// que sinaliza estável cedo demais.
+pub struct StabilityFilter {
+    window: usize,
+    band: Mass,
+    recent: VecDeque<Mass>,
+}
+
+impl StabilityFilter {
+    pub fn new(window: usize, band: Mass) -> Self {
+        StabilityFilter { window: window.max(1), band, recent: VecDeque::new() }
+    }
+
+    /// Registra a leitura e diz se o peso está estável.
+    pub fn push(&mut self, reading: &Reading) -> bool {
+        if !reading.stable {
+            self.recent.clear();
+            return false;
+        }
+        self.recent.push_back(reading.weight);
+        if self.recent.len() > self.window {
+            self.recent.pop_front();
+        }
+        match (self.recent.iter().min(), self.recent.iter().max()) {
+            (Some(&min), Some(&max)) => self.recent.len() == self.window && max - min <= self.band,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_consecutive_stable_readings() {
+        let leitura = |kg: f64, stable| Reading { weight: Mass::from_kg(kg), tare: None, stable };
+        let mut filtro = StabilityFilter::new(3, Mass::from_kg(0.002));
+        assert!(!filtro.push(&leitura(10.0, true)));
+        assert!(!filtro.push(&leitura(10.001, true)));
+        assert!(filtro.push(&leitura(10.002, true)));
+        // saiu da faixa
+        assert!(!filtro.push(&leitura(10.010, true)));
+        // movimento zera a janela
+        assert!(!filtro.push(&leitura(10.010, false)));
+        assert!(!filtro.push(&leitura(10.010, true)));
+        assert!(!filtro.push(&leitura(10.010, true)));
+        assert!(filtro.push(&leitura(10.010, true)));
+    }
+}
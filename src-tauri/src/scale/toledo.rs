@@ -0,0 +1,92 @@
+use super::protocol::{mass_in, Frame, Protocol, Reading};
+
+const STX: u8 = 0x02;
+const CR: u8 = b'\r';
+/// STX, três palavras de estado, peso e tara com seis dígitos cada e CR.
+const FRAME_LEN: usize = 17;
+
+/// Saída contínua Toledo: a balança transmite o quadro o tempo todo, com o
+/// peso sem ponto decimal e a posição do ponto nas palavras de estado.
+/// Tara e zero são os caracteres `T` e `Z`; a balança não os confirma, e o
+/// resultado só aparece nos quadros seguintes (nunca há `Frame::Ack`).
+pub struct Toledo;
+
+impl Protocol for Toledo {
+    fn poll(&self) -> Option<&'static [u8]> {
+        None
+    }
+
+    fn tare(&self) -> &'static [u8] {
+        b"T"
+    }
+
+    fn zero(&self) -> &'static [u8] {
+        b"Z"
+    }
+
+    fn next_frame(&self, buf: &mut Vec<u8>) -> Option<Vec<u8>> {
+        loop {
+            // descarta o que vier antes do STX (checksum, lixo da linha)
+            let start = buf.iter().position(|&b| b == STX)?;
+            buf.drain(..start);
+            if buf.len() < FRAME_LEN {
+                return None;
+            }
+            if buf[FRAME_LEN - 1] == CR {
+                return Some(buf.drain(..FRAME_LEN).collect());
+            }
+            // STX no meio de um quadro truncado: procura o próximo
+            buf.remove(0);
+        }
+    }
+
+    fn parse(&self, frame: &[u8]) -> Option<Frame> {
+        if frame.len() != FRAME_LEN {
+            return None;
+        }
+        let (swa, swb) = (frame[1], frame[2]);
+        if swb & 0x04 != 0 {
+            return Some(Frame::Error("Balança fora da faixa".to_string()));
+        }
+        // bits 0-2 da palavra A: 0 = x100 ... 2 = x1, 3 = 0.1 ... 7 = 0.00001
+        let scale = 10f64.powi(2 - (swa & 0x07) as i32);
+        let number = |digits: &[u8]| -> Option<f64> {
+            std::str::from_utf8(digits).ok()?.trim().parse::<f64>().ok().map(|n| n * scale)
+        };
+        let unit = if swb & 0x10 != 0 { "kg" } else { "lb" };
+        let sign = if swb & 0x02 != 0 { -1.0 } else { 1.0 };
+        let weight = mass_in(sign * number(&frame[4..10])?, unit)?;
+        // a tara só vale quando o peso indicado é líquido
+        let tare = if swb & 0x01 != 0 { Some(mass_in(number(&frame[10..16])?, unit)?) } else { None };
+        Some(Frame::Weight(Reading { weight, tare, stable: swb & 0x08 == 0 }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quantity::Mass;
+
+    #[test]
+    fn test_parse_toledo() {
+        let p = Toledo;
+        // ponto em 0.001, líquido, kg, estável: 12,345 kg com 0,500 kg de tara
+        let quadro = b"\x02\x25\x31\x20012345000500\r";
+        let mut buf = [b"\x99\x02\x25".as_slice(), quadro, b"\x02\x25"].concat();
+        let frame = p.next_frame(&mut buf).unwrap();
+        assert_eq!(frame, quadro);
+        assert_eq!(buf, b"\x02\x25");
+        assert_eq!(
+            p.parse(&frame),
+            Some(Frame::Weight(Reading { weight: Mass::from_kg(12.345), tare: Some(Mass::from_kg(0.5)), stable: true }))
+        );
+
+        // bruto, negativo, em movimento
+        let frame = b"\x02\x24\x3a\x20000150000000\r";
+        assert_eq!(
+            p.parse(frame),
+            Some(Frame::Weight(Reading { weight: Mass::from_kg(-1.5), tare: None, stable: false }))
+        );
+        assert!(matches!(p.parse(b"\x02\x24\x34\x20000000000000\r"), Some(Frame::Error(_))));
+    }
+}
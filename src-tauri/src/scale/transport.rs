@@ -0,0 +1,66 @@
+use serde::{Serialize, Deserialize};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use super::driver::Link;
+
+/// Espera máxima de cada leitura; limita a demora para atender tara, zero
+/// e desconexão.
+pub const READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Por onde a estação fala com a balança.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Transport {
+    /// Porta serial ou USB-serial: `COM3`, `/dev/ttyUSB0`...
+    Serial {
+        port: String,
+        #[serde(default = "default_baud_rate")]
+        baud_rate: u32,
+    },
+    /// Indicador Ethernet em socket TCP cru.
+    Tcp { host: String, port: u16 },
+}
+
+fn default_baud_rate() -> u32 {
+    9600
+}
+
+impl Transport {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Transport::Serial { port, baud_rate } if port.trim().is_empty() || *baud_rate == 0 => {
+                Err("Informe a porta serial e a velocidade".to_string())
+            }
+            Transport::Tcp { host, port } if host.trim().is_empty() || *port == 0 => {
+                Err("Informe o endereço e a porta TCP".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn open(&self) -> Result<Box<dyn Link>, String> {
+        match self {
+            Transport::Serial { port, baud_rate } => {
+                let serial = serialport::new(port, *baud_rate)
+                    .timeout(READ_TIMEOUT)
+                    .open()
+                    .map_err(|e| format!("Não foi possível abrir a porta {}: {}", port, e))?;
+                Ok(Box::new(serial))
+            }
+            Transport::Tcp { host, port } => {
+                let unreachable = |e: std::io::Error| format!("Não foi possível conectar a {}:{}: {}", host, port, e);
+                let addr = (host.as_str(), *port).to_socket_addrs().map_err(unreachable)?
+                    .next()
+                    .ok_or(format!("Endereço {} não encontrado", host))?;
+                let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(unreachable)?;
+                stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(unreachable)?;
+                // indicador que parou de ler não pode travar a thread
+                stream.set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(unreachable)?;
+                stream.set_nodelay(true).map_err(unreachable)?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ import FornecedoresView from "./views/FornecedoresView";
 import ItensView from "./views/ItensView";
 import UsersView from "./views/UsersView";
 import SprintsView from "./views/SprintsView";
+import ScalesView from "./views/ScalesView";
 import { NavigationProvider, useNavigation } from './NavigationContext';
 import NewFornecedorView from './views/NewFornecedorView';
 import NewItemView from './views/NewItemView';
@@ -18,6 +19,8 @@ import NewSprintView from './views/NewSprintView';
 import SprintExecutionView from './views/SprintExecutionView';
 import ProcessoDashboardView from './views/ProcessoDashboardView';
 import { TrialChecker } from './components/TrialChecker';
+import { SessionProvider, useSession } from './SessionContext';
+import LoginView from './views/LoginView';
 
 function SprintExecutionWrapper() {
   const { payload, navigate } = useNavigation();
@@ -50,6 +53,8 @@ function Content() {
       return <ItensView />;
     case 'cadastros-users':
       return <UsersView />;
+    case 'balancas':
+      return <ScalesView />;
     case 'novo-fornecedor':
       return <NewFornecedorView />;
     case 'novo-item':
@@ -73,18 +78,32 @@ function Content() {
   }
 }
 
+function AuthenticatedApp() {
+  const { token, mustChangePassword } = useSession();
+
+  if (!token || mustChangePassword) {
+    return <LoginView />;
+  }
+
+  return (
+    <div className="app-root">
+      <div className="app-layout">
+        <Sidebar />
+        <div className="content">
+          <Content />
+        </div>
+      </div>
+    </div>
+  );
+}
+
 function App() {
   return (
     <NavigationProvider>
       <TrialChecker>
-        <div className="app-root">
-          <div className="app-layout">
-            <Sidebar />
-            <div className="content">
-              <Content />
-            </div>
-          </div>
-        </div>
+        <SessionProvider>
+          <AuthenticatedApp />
+        </SessionProvider>
       </TrialChecker>
     </NavigationProvider>
   );
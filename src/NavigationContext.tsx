@@ -8,6 +8,7 @@ export type RouteKey =
   | 'cadastros-fornecedores'
   | 'cadastros-itens'
   | 'cadastros-users'
+  | 'balancas'
   | 'novo-fornecedor'
   | 'novo-item'
   | 'nova-formula'
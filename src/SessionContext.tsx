@@ -0,0 +1,73 @@
+import React, { createContext, useContext, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { setSessionToken } from './api';
+
+export interface SessionUser {
+  id: string;
+  username: string;
+  role: string;
+  must_change_password: boolean;
+}
+
+interface LoginResponse {
+  token: string;
+  user: SessionUser;
+  must_change_password: boolean;
+  permissions: string[];
+}
+
+interface SessionState {
+  token: string | null;
+  user: SessionUser | null;
+  mustChangePassword: boolean;
+  can: (permission: string) => boolean;
+  login: (username: string, password: string) => Promise<void>;
+  logout: () => Promise<void>;
+  changePassword: (currentPassword: string, newPassword: string) => Promise<void>;
+}
+
+const SessionContext = createContext<SessionState | undefined>(undefined);
+
+export const SessionProvider: React.FC<{ children: React.ReactNode }> = ({ children }) => {
+  const [token, setToken] = useState<string | null>(null);
+  const [user, setUser] = useState<SessionUser | null>(null);
+  const [mustChangePassword, setMustChangePassword] = useState(false);
+  const [permissions, setPermissions] = useState<string[]>([]);
+
+  const can = (permission: string) => permissions.includes(permission);
+
+  const login = async (username: string, password: string) => {
+    const res = await invoke<LoginResponse>('login', { username, password });
+    setSessionToken(res.token);
+    setToken(res.token);
+    setUser(res.user);
+    setPermissions(res.permissions);
+    setMustChangePassword(res.must_change_password);
+  };
+
+  const logout = async () => {
+    if (token) await invoke('logout', { token });
+    setSessionToken(null);
+    setToken(null);
+    setUser(null);
+    setPermissions([]);
+    setMustChangePassword(false);
+  };
+
+  const changePassword = async (currentPassword: string, newPassword: string) => {
+    await invoke('change_password', { token, currentPassword, newPassword });
+    setMustChangePassword(false);
+  };
+
+  return (
+    <SessionContext.Provider value={{ token, user, mustChangePassword, can, login, logout, changePassword }}>
+      {children}
+    </SessionContext.Provider>
+  );
+};
+
+export const useSession = () => {
+  const ctx = useContext(SessionContext);
+  if (!ctx) throw new Error('useSession must be used inside SessionProvider');
+  return ctx;
+};
@@ -0,0 +1,149 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// Token da sessão atual; definido pelo SessionProvider no login/logout
+let sessionToken: string | null = null;
+
+export const setSessionToken = (token: string | null) => { sessionToken = token; };
+
+// Invoca um comando que exige usuário autenticado, anexando o token da sessão
+export function invokeAuth<T = unknown>(cmd: string, args: Record<string, unknown> = {}): Promise<T> {
+  return invoke<T>(cmd, { ...args, token: sessionToken });
+}
+
+// Página retornada por listagens e buscas: itens da página e total de resultados.
+// `cursor` é opaco; repassá-lo busca a página seguinte sem reler as anteriores.
+export interface Page<T> {
+  items: T[];
+  total: number;
+  page: number;
+  page_size: number;
+  cursor: string | null;
+}
+
+// Parâmetros de ordenação aceitos pelos comandos list_*
+export type SortField = 'name' | 'created_at' | 'updated_at' | 'status';
+export type SortDirection = 'asc' | 'desc';
+
+// Situação do processo e as que ainda aceitam sprints
+export type ProcessoStatus = 'Planned' | 'InProgress' | 'Paused' | 'Finished' | 'Cancelled';
+
+export const processoStatusLabels: Record<ProcessoStatus, string> = {
+  Planned: 'Planejado',
+  InProgress: 'Em Andamento',
+  Paused: 'Pausado',
+  Finished: 'Finalizado',
+  Cancelled: 'Cancelado',
+};
+
+export const isProcessoAtivo = (status: ProcessoStatus) => status === 'Planned' || status === 'InProgress';
+
+// Estratégia de compensação do erro acumulado entre sprints
+export type CompensationStrategy =
+  | { kind: 'full_carry' }
+  | { kind: 'spread' }
+  | { kind: 'capped'; max_pct: number }
+  | { kind: 'none' }
+  | { kind: 'ratio_preserving' };
+
+// Massas (alvos, pesos, leituras, divisões da balança) trafegam em mg
+// inteiros; as telas mostram e recebem kg
+export const kgFromMg = (mg: number) => mg / 1_000_000;
+export const mgFromKg = (kg: number) => Math.round(kg * 1_000_000);
+
+// Unidades de medida; o backend calcula tudo em kg
+export type Unit = 'kg' | 'g' | 'mg' | 'l' | 'ml' | 'un';
+
+export const unitLabels: Record<Unit, string> = {
+  kg: 'kg',
+  g: 'g',
+  mg: 'mg',
+  l: 'L',
+  ml: 'mL',
+  un: 'un',
+};
+
+export const unitOptions = (Object.keys(unitLabels) as Unit[]).map(u => ({ key: u, text: unitLabels[u] }));
+
+// Volume e unidades contadas precisam de densidade no item
+export const unitNeedsDensity = (unit: Unit) => unit === 'l' || unit === 'ml' || unit === 'un';
+
+// Balanças: pesos em mg, publicados pelo backend no evento `scale-event`
+export type ScaleProtocol = 'mtsics' | 'toledo';
+
+export const scaleProtocolOptions: { key: ScaleProtocol; text: string }[] = [
+  { key: 'mtsics', text: 'MT-SICS (Mettler Toledo)' },
+  { key: 'toledo', text: 'Toledo saída contínua' },
+];
+
+// Por onde a estação fala com a balança
+export type ScaleTransport =
+  | { kind: 'serial'; port: string; baud_rate: number }
+  | { kind: 'tcp'; host: string; port: number };
+
+export const describeTransport = (t: ScaleTransport) =>
+  t.kind === 'serial' ? `${t.port} @ ${t.baud_rate}` : `${t.host}:${t.port}`;
+
+export interface ScaleConfig {
+  id: string;
+  name: string;
+  transport: ScaleTransport;
+  protocol: ScaleProtocol;
+  stable_readings: number;
+  stable_band: number;
+  poll_interval_ms: number;
+}
+
+export interface ScaleReading {
+  weight: number;
+  tare: number | null;
+  // já confirmado pelo filtro de estabilidade
+  stable: boolean;
+}
+
+// Situação da conexão; fora de linha o backend tenta de novo sozinho
+export type ScaleHealth =
+  | { state: 'connecting' }
+  | { state: 'online' }
+  | { state: 'offline'; error: string; attempt: number; retry_in_ms: number };
+
+export interface ScaleStatus {
+  config: ScaleConfig;
+  health: ScaleHealth;
+  reading: ScaleReading | null;
+  reading_age_ms: number | null;
+  error: string | null;
+}
+
+// Balança ligada ao simulador de treinamento
+export const SIMULATOR_ID = 'simulador';
+
+export type ScaleEvent =
+  | { type: 'reading'; scale_id: string; reading: ScaleReading }
+  | { type: 'acknowledged'; scale_id: string }
+  | { type: 'error'; scale_id: string; message: string }
+  | { type: 'health'; scale_id: string; health: ScaleHealth };
+
+// Assina os eventos das balanças; devolve a função que cancela a assinatura
+export const listenScales = (handler: (event: ScaleEvent) => void) =>
+  listen<ScaleEvent>('scale-event', e => handler(e.payload));
+
+// Aplica um evento à lista de situações mostrada na tela
+export const applyScaleEvent = (scales: ScaleStatus[], event: ScaleEvent): ScaleStatus[] =>
+  scales.map(s => {
+    if (s.config.id !== event.scale_id) return s;
+    switch (event.type) {
+      case 'reading': return { ...s, reading: event.reading, reading_age_ms: 0, error: null };
+      case 'acknowledged': return { ...s, error: null };
+      case 'error': return { ...s, error: event.message };
+      case 'health': return { ...s, health: event.health, error: event.health.state === 'offline' ? event.health.error : s.error };
+    }
+  });
+
+export const healthLabel = (h: ScaleHealth) =>
+  h.state === 'online' ? '● Em linha'
+    : h.state === 'connecting' ? '● Conectando...'
+    : `● Fora de linha (tentativa ${h.attempt}, nova em ${Math.round(h.retry_in_ms / 1000)} s)`;
+
+export const healthColor = (h: ScaleHealth) =>
+  h.state === 'online' ? '#107c10' : h.state === 'connecting' ? '#8a6d00' : '#d13438';
@@ -0,0 +1,52 @@
+import { Dropdown, IDropdownOption, Stack, TextField } from '@fluentui/react';
+import { CompensationStrategy } from '../api';
+
+interface Props {
+  value: CompensationStrategy;
+  onChange: (value: CompensationStrategy) => void;
+  mixerCapacity: string;
+  onMixerCapacityChange: (value: string) => void;
+}
+
+const options: IDropdownOption[] = [
+  { key: 'full_carry', text: 'Integral no próximo sprint' },
+  { key: 'spread', text: 'Distribuída nos sprints restantes' },
+  { key: 'capped', text: 'Limitada por sprint (%)' },
+  { key: 'none', text: 'Sem compensação' },
+  { key: 'ratio_preserving', text: 'Manter proporção no próprio sprint' },
+];
+
+export default function CompensationPicker({ value, onChange, mixerCapacity, onMixerCapacityChange }: Props) {
+  const handleKind = (kind: string) => {
+    onChange(kind === 'capped' ? { kind: 'capped', max_pct: 5 } : { kind } as CompensationStrategy);
+  };
+
+  return (
+    <Stack horizontal tokens={{ childrenGap: 12 }} styles={{ root: { alignItems: 'flex-end' } }}>
+      <Dropdown
+        label="Compensação do erro"
+        options={options}
+        selectedKey={value.kind}
+        onChange={(_, o) => handleKind(o?.key as string)}
+        styles={{ root: { minWidth: 280 } }}
+      />
+      {value.kind === 'capped' && (
+        <TextField
+          label="Correção máxima (% do peso base)"
+          value={String(value.max_pct)}
+          onChange={(_, v) => onChange({ kind: 'capped', max_pct: parseFloat(v || '0') || 0 })}
+          styles={{ root: { width: 220 } }}
+        />
+      )}
+      {value.kind === 'ratio_preserving' && (
+        <TextField
+          label="Capacidade do misturador (kg)"
+          value={mixerCapacity}
+          onChange={(_, v) => onMixerCapacityChange(v || '')}
+          placeholder="Sem limite"
+          styles={{ root: { width: 220 } }}
+        />
+      )}
+    </Stack>
+  );
+}
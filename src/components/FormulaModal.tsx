@@ -1,8 +1,8 @@
 import { useState, useEffect } from 'react';
-import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, Stack, Dropdown } from '@fluentui/react';
+import { invokeAuth, Page, Unit, unitLabels, unitOptions } from '../api';
 
-interface ItemOption { id: string; nome: string }
+interface ItemOption { id: string; nome: string; unit: Unit }
 
 interface Props {
   hidden: boolean;
@@ -15,25 +15,41 @@ export default function FormulaModal({ hidden, onDismiss, onSaved }: Props) {
   const [items, setItems] = useState<ItemOption[]>([]);
   const [selectedItemId, setSelectedItemId] = useState('');
   const [peso, setPeso] = useState('0');
-  const [rows, setRows] = useState<Array<{itemId:string,peso:number,nome:string}>>([]);
+  const [unit, setUnit] = useState<Unit>('kg');
+  const [tolAbs, setTolAbs] = useState('');
+  const [tolPct, setTolPct] = useState('');
+  const [doseMin, setDoseMin] = useState('');
+  const [doseMax, setDoseMax] = useState('');
+  const [rows, setRows] = useState<Array<{itemId:string,peso:number,unit:Unit,nome:string,tolAbs:number|null,tolPct:number|null,min:number|null,max:number|null}>>([]);
   const [saving, setSaving] = useState(false);
 
   useEffect(() => { if (!hidden) loadItems(); }, [hidden]);
 
   const loadItems = async () => {
     try {
-      const it = await invoke<any[]>('list_itens', { page: 0, pageSize: 100 });
-      const opts = it.map(i => ({ id: i.id, nome: i.nome }));
+      const it = (await invokeAuth<Page<any>>('list_itens', { page: 0, pageSize: 100, sortBy: 'name' })).items;
+      const opts = it.map(i => ({ id: i.id, nome: i.nome, unit: i.unit ?? 'kg' }));
       setItems(opts);
-      if (opts.length > 0) setSelectedItemId(opts[0].id);
+      if (opts.length > 0) { setSelectedItemId(opts[0].id); setUnit(opts[0].unit); }
     } catch (e) { console.error(e); }
   };
 
+  // a linha começa na unidade padrão do item
+  const selectItem = (id: string) => {
+    setSelectedItemId(id);
+    const it = items.find(i => i.id === id);
+    if (it) setUnit(it.unit ?? 'kg');
+  };
+
   const addRow = () => {
     if (!selectedItemId) return;
     const it = items.find(i => i.id === selectedItemId);
     if (!it) return;
-    setRows([...rows, { itemId: selectedItemId, peso: parseFloat(peso) || 0, nome: it.nome }]);
+    // tolerância e limites em branco = sem restrição
+    const parseTol = (v: string) => v.trim() === '' ? null : parseFloat(v);
+    const min = parseTol(doseMin), max = parseTol(doseMax);
+    if (min !== null && max !== null && min > max) { alert('Dose mínima maior que a máxima'); return; }
+    setRows([...rows, { itemId: selectedItemId, peso: parseFloat(peso) || 0, unit, nome: it.nome, tolAbs: parseTol(tolAbs), tolPct: parseTol(tolPct), min, max }]);
   };
 
   const handleSave = async () => {
@@ -41,8 +57,8 @@ export default function FormulaModal({ hidden, onDismiss, onSaved }: Props) {
     if (rows.length === 0) { alert('Adicione ao menos 1 item com peso'); return; }
     try {
       setSaving(true);
-      const itensPayload = rows.map(r => [r.itemId, r.peso]);
-      await invoke('create_formula', { nome, itens: itensPayload });
+      const itensPayload = rows.map(r => ({ item_id: r.itemId, peso: r.peso, unit: r.unit, tolerance: { abs: r.tolAbs, pct: r.tolPct }, limits: { min: r.min, max: r.max } }));
+      await invokeAuth('create_formula', { nome, itens: itensPayload });
       onSaved();
       setNome(''); setRows([]);
       onDismiss();
@@ -54,12 +70,17 @@ export default function FormulaModal({ hidden, onDismiss, onSaved }: Props) {
       <Stack tokens={{ childrenGap: 12 }}>
         <TextField label="Nome" value={nome} onChange={(_, v) => setNome(v || '')} />
         <Stack horizontal tokens={{ childrenGap: 8 }} verticalAlign="end" styles={{ root: { flexWrap: 'wrap' } }}>
-          <ComboBox label="Item" options={items.map(i => ({ key: i.id, text: i.nome }))} selectedKey={selectedItemId} onChange={(_, o) => setSelectedItemId(o?.key as string || '')} allowFreeform autoComplete="on" styles={{ root: { minWidth: 260 } }} />
-          <TextField label="Peso (kg por sprint)" value={peso} onChange={(_, v) => setPeso(v || '0')} styles={{ root: { minWidth: 180 } }} />
+          <ComboBox label="Item" options={items.map(i => ({ key: i.id, text: i.nome }))} selectedKey={selectedItemId} onChange={(_, o) => selectItem(o?.key as string || '')} allowFreeform autoComplete="on" styles={{ root: { minWidth: 260 } }} />
+          <TextField label={`Peso (${unitLabels[unit]} por sprint)`} value={peso} onChange={(_, v) => setPeso(v || '0')} styles={{ root: { minWidth: 180 } }} />
+          <Dropdown label="Unidade" options={unitOptions} selectedKey={unit} onChange={(_, o) => setUnit(o?.key as Unit)} styles={{ root: { width: 100 } }} />
+          <TextField label={`Tolerância (± ${unitLabels[unit]})`} value={tolAbs} onChange={(_, v) => setTolAbs(v || '')} styles={{ root: { width: 130 } }} />
+          <TextField label="Tolerância (± %)" value={tolPct} onChange={(_, v) => setTolPct(v || '')} styles={{ root: { width: 130 } }} />
+          <TextField label={`Dose mín. (${unitLabels[unit]})`} value={doseMin} onChange={(_, v) => setDoseMin(v || '')} placeholder="Opcional" styles={{ root: { width: 120 } }} />
+          <TextField label={`Dose máx. (${unitLabels[unit]})`} value={doseMax} onChange={(_, v) => setDoseMax(v || '')} placeholder="Opcional" styles={{ root: { width: 120 } }} />
           <PrimaryButton text="Adicionar" onClick={addRow} />
         </Stack>
         <div style={{ marginTop: 12 }}>
-          {rows.map((r, i) => (<div key={i} style={{ padding: '6px 0', borderBottom: '1px solid #eee' }}>{r.nome} — <strong>{r.peso} kg</strong></div>))}
+          {rows.map((r, i) => (<div key={i} style={{ padding: '6px 0', borderBottom: '1px solid #eee' }}>{r.nome} — <strong>{r.peso} {unitLabels[r.unit]}</strong>{r.tolAbs !== null && ` ±${r.tolAbs} ${unitLabels[r.unit]}`}{r.tolPct !== null && ` ±${r.tolPct}%`}{r.min !== null && ` · mín. ${r.min} ${unitLabels[r.unit]}`}{r.max !== null && ` · máx. ${r.max} ${unitLabels[r.unit]}`}</div>))}
         </div>
       </Stack>
       <DialogFooter>
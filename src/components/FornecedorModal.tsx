@@ -1,6 +1,6 @@
 import { useState } from 'react';
 import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth } from '../api';
 
 interface Props {
   hidden: boolean;
@@ -19,7 +19,7 @@ export default function FornecedorModal({ hidden, onDismiss, onSaved }: Props) {
     }
     try {
       setSaving(true);
-      await invoke('create_fornecedor', { nome });
+      await invokeAuth('create_fornecedor', { nome });
       onSaved();
       setNome('');
       onDismiss();
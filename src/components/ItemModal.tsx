@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
-import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, IComboBoxOption, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, IComboBoxOption, Stack, Dropdown } from '@fluentui/react';
+import { invokeAuth, mgFromKg, Page, Unit, unitOptions, unitNeedsDensity } from '../api';
 
 interface Props {
   hidden: boolean;
@@ -12,6 +12,9 @@ export default function ItemModal({ hidden, onDismiss, onSaved }: Props) {
   const [nome, setNome] = useState('');
   const [fornecedores, setFornecedores] = useState<IComboBoxOption[]>([]);
   const [fornecedorId, setFornecedorId] = useState<string>('');
+  const [unit, setUnit] = useState<Unit>('kg');
+  const [density, setDensity] = useState('');
+  const [resolution, setResolution] = useState('');
   const [saving, setSaving] = useState(false);
 
   useEffect(() => {
@@ -20,7 +23,7 @@ export default function ItemModal({ hidden, onDismiss, onSaved }: Props) {
 
   const loadFornecedores = async () => {
     try {
-      const f = await invoke<any[]>('list_fornecedores', { page: 0, pageSize: 100 });
+      const f = (await invokeAuth<Page<any>>('list_fornecedores', { page: 0, pageSize: 100, sortBy: 'name' })).items;
       const opts = f.map(ff => ({ key: ff.id, text: ff.nome }));
       setFornecedores(opts);
       if (opts.length > 0) setFornecedorId(opts[0].key as string);
@@ -32,9 +35,12 @@ export default function ItemModal({ hidden, onDismiss, onSaved }: Props) {
   const handleSave = async () => {
     if (!nome.trim()) { alert('Nome é obrigatório'); return; }
     if (!fornecedorId) { alert('Selecione um fornecedor'); return; }
+    if (unitNeedsDensity(unit) && !(parseFloat(density) > 0)) { alert('Informe a densidade do item'); return; }
     try {
       setSaving(true);
-      await invoke('create_item', { nome, fornecedorId });
+      // campos em branco = não informado
+      const optional = (v: string) => v.trim() ? parseFloat(v) : null;
+      await invokeAuth('create_item', { nome, fornecedorId, measure: { unit, density: optional(density), resolution: resolution.trim() ? mgFromKg(parseFloat(resolution)) : null } });
       onSaved();
       setNome('');
       onDismiss();
@@ -48,6 +54,18 @@ export default function ItemModal({ hidden, onDismiss, onSaved }: Props) {
           <TextField label="Nome" value={nome} onChange={(_, v) => setNome(v || '')} styles={{ root: { minWidth: 420 } }} />
           <ComboBox label="Fornecedor" options={fornecedores} selectedKey={fornecedorId} onChange={(_, o) => setFornecedorId(o?.key as string || '')} allowFreeform autoComplete="on" styles={{ root: { minWidth: 320 } }} />
         </Stack>
+        <Stack horizontal tokens={{ childrenGap: 12 }}>
+          <Dropdown label="Unidade" options={unitOptions} selectedKey={unit} onChange={(_, o) => setUnit(o?.key as Unit)} styles={{ root: { width: 120 } }} />
+          {unitNeedsDensity(unit) && (
+            <TextField
+              label={unit === 'un' ? 'Massa por unidade (kg)' : 'Densidade (kg/L)'}
+              value={density}
+              onChange={(_, v) => setDensity(v || '')}
+              styles={{ root: { width: 200 } }}
+            />
+          )}
+          <TextField label="Divisão da balança (kg)" value={resolution} onChange={(_, v) => setResolution(v || '')} placeholder="Opcional" styles={{ root: { width: 200 } }} />
+        </Stack>
         <div style={{ color: '#605e5c', fontSize: 13 }}>Escolha o fornecedor existente ou busque por nome.</div>
       </Stack>
       <DialogFooter>
@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react';
 import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, IComboBoxOption, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, mgFromKg, Page, CompensationStrategy } from '../api';
+import CompensationPicker from './CompensationPicker';
 
 interface Props {
   hidden: boolean;
@@ -12,6 +13,11 @@ export default function ProcessCreationModal({ hidden, onDismiss, onSaved }: Pro
   const [nome, setNome] = useState('');
   const [formulas, setFormulas] = useState<IComboBoxOption[]>([]);
   const [formulaId, setFormulaId] = useState('');
+  const [compensation, setCompensation] = useState<CompensationStrategy>({ kind: 'full_carry' });
+  const [mixerCapacity, setMixerCapacity] = useState('');
+  const [targetMass, setTargetMass] = useState('');
+  const [plannedSprints, setPlannedSprints] = useState('');
+  const [resolution, setResolution] = useState('');
   const [saving, setSaving] = useState(false);
 
   useEffect(() => {
@@ -20,7 +26,7 @@ export default function ProcessCreationModal({ hidden, onDismiss, onSaved }: Pro
 
   const loadFormulas = async () => {
     try {
-      const f = await invoke<any[]>('list_formulas', { page: 0, pageSize: 200 });
+      const f = (await invokeAuth<Page<any>>('list_formulas', { page: 0, pageSize: 200, sortBy: 'name' })).items;
       const opts = f.map(ff => ({ key: ff.id, text: ff.nome }));
       setFormulas(opts);
       if (opts.length > 0) setFormulaId(opts[0].key as string);
@@ -34,7 +40,19 @@ export default function ProcessCreationModal({ hidden, onDismiss, onSaved }: Pro
     if (!formulaId) { alert('Selecione uma fórmula'); return; }
     try {
       setSaving(true);
-      await invoke('create_processo', { nome, formulaId });
+      // campos em branco = sem limite / sem planejamento
+      const optional = (v: string, parse: (v: string) => number) => v.trim() ? parse(v) : null;
+      await invokeAuth('create_processo', {
+        nome,
+        formulaId,
+        settings: {
+          compensation,
+          mixer_capacity: optional(mixerCapacity, parseFloat),
+          target_mass: optional(targetMass, parseFloat),
+          planned_sprints: optional(plannedSprints, v => parseInt(v, 10)),
+          resolution: optional(resolution, v => mgFromKg(parseFloat(v))),
+        }
+      });
       onSaved();
       setNome('');
       onDismiss();
@@ -58,8 +76,14 @@ export default function ProcessCreationModal({ hidden, onDismiss, onSaved }: Pro
           <TextField label="Nome do Processo" value={nome} onChange={(_, v) => setNome(v || '')} styles={{ root: { minWidth: 420 } }} />
           <ComboBox label="Fórmula" options={formulas} selectedKey={formulaId} onChange={(_, o) => setFormulaId(o?.key as string || '')} allowFreeform autoComplete="on" styles={{ root: { minWidth: 320 } }} />
         </Stack>
+        <Stack horizontal tokens={{ childrenGap: 12 }}>
+          <TextField label="Massa total planejada (kg)" value={targetMass} onChange={(_, v) => setTargetMass(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+          <TextField label="Nº de sprints planejados" value={plannedSprints} onChange={(_, v) => setPlannedSprints(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+          <TextField label="Divisão da balança (kg)" value={resolution} onChange={(_, v) => setResolution(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+        </Stack>
+        <CompensationPicker value={compensation} onChange={setCompensation} mixerCapacity={mixerCapacity} onMixerCapacityChange={setMixerCapacity} />
         <div style={{ color: '#605e5c', fontSize: 13 }}>
-          Preencha o nome e escolha a fórmula. O peso total do processo será calculado automaticamente a partir dos itens da fórmula.
+          Preencha o nome e escolha a fórmula. Informe a massa total e/ou o número de sprints para planejar o lote; sem eles, cada sprint segue os pesos da fórmula.
         </div>
       </Stack>
 
@@ -0,0 +1,74 @@
+import { useEffect, useState } from 'react';
+import { DefaultButton, Dropdown, PrimaryButton, Stack } from '@fluentui/react';
+import { invokeAuth, kgFromMg, listenScales, applyScaleEvent, healthColor, healthLabel, ScaleStatus } from '../api';
+
+interface Props {
+  // lança o último peso estável da balança escolhida
+  onCapture: (scaleId: string) => void;
+  disabled?: boolean;
+}
+
+// Peso ao vivo da balança conectada, com tara/zero e captura do peso estável
+export default function ScalePanel({ onCapture, disabled }: Props) {
+  const [scales, setScales] = useState<ScaleStatus[]>([]);
+  const [selected, setSelected] = useState('');
+
+  useEffect(() => {
+    invokeAuth<ScaleStatus[]>('list_scales')
+      .then(list => {
+        setScales(list);
+        const first = list.find(s => s.health.state === 'online') ?? list[0];
+        if (first) setSelected(first.config.id);
+      })
+      .catch(e => console.error(e));
+    const unlisten = listenScales(event => setScales(prev => applyScaleEvent(prev, event)));
+    return () => { unlisten.then(f => f()); };
+  }, []);
+
+  if (scales.length === 0) return null;
+  const scale = scales.find(s => s.config.id === selected) ?? scales[0];
+  const online = scale.health.state === 'online';
+  const reading = online ? scale.reading : null;
+
+  const send = async (cmd: 'tare_scale' | 'zero_scale' | 'reconnect_scale') => {
+    try {
+      await invokeAuth(cmd, { scaleId: scale.config.id });
+    } catch (e) {
+      alert('❌ ' + e);
+    }
+  };
+
+  return (
+    <div className="target-weight-card">
+      <Stack horizontal tokens={{ childrenGap: 12 }} verticalAlign="end">
+        {scales.length > 1 && (
+          <Dropdown
+            label="Balança"
+            options={scales.map(s => ({ key: s.config.id, text: s.config.name || s.config.id }))}
+            selectedKey={scale.config.id}
+            onChange={(_, o) => setSelected(o?.key as string)}
+            styles={{ root: { width: 200 } }}
+          />
+        )}
+        <div>
+          <div className="target-label">
+            ⚖️ {scale.config.name || scale.config.id} <span style={{ color: healthColor(scale.health) }}>{healthLabel(scale.health)}</span>
+          </div>
+          <div className="target-value" style={{ color: reading?.stable ? '#107c10' : '#8a6d00' }}>
+            {reading ? `${kgFromMg(reading.weight).toFixed(3)} kg` : '—'}
+          </div>
+          <div className="target-hint">
+            {scale.error ? `⚠️ ${scale.error}` : !online ? '' : reading?.stable ? 'Estável' : 'Aguardando estabilizar...'}
+            {reading?.tare != null && ` · tara ${kgFromMg(reading.tare).toFixed(3)} kg`}
+          </div>
+        </div>
+      </Stack>
+      <Stack horizontal tokens={{ childrenGap: 8 }} styles={{ root: { marginTop: 8 } }}>
+        <PrimaryButton text="⚖️ Capturar peso" onClick={() => onCapture(scale.config.id)} disabled={disabled || !reading?.stable} />
+        <DefaultButton text="Tara" onClick={() => send('tare_scale')} disabled={disabled || !online} />
+        <DefaultButton text="Zero" onClick={() => send('zero_scale')} disabled={disabled || !online} />
+        {!online && <DefaultButton text="🔄 Reconectar" onClick={() => send('reconnect_scale')} />}
+      </Stack>
+    </div>
+  );
+}
@@ -1,11 +1,13 @@
 import { Nav } from '@fluentui/react';
 
-export type SidebarSelect = 'home' | 'processos' | 'sprints' | 'cadastros-formulas' | 'cadastros-fornecedores' | 'cadastros-itens' | 'cadastros-users' | 'novo-fornecedor' | 'novo-item' | 'nova-formula' | 'novo-usuario' | 'novo-processo' | 'novo-sprint';
+export type SidebarSelect = 'home' | 'processos' | 'sprints' | 'cadastros-formulas' | 'cadastros-fornecedores' | 'cadastros-itens' | 'cadastros-users' | 'balancas' | 'novo-fornecedor' | 'novo-item' | 'nova-formula' | 'novo-usuario' | 'novo-processo' | 'novo-sprint';
 
 import { useNavigation } from '../NavigationContext';
+import { useSession } from '../SessionContext';
 
 export default function Sidebar() {
   const nav = useNavigation();
+  const { can } = useSession();
 
   return (
     <nav className="app-sidebar">
@@ -25,7 +27,8 @@ export default function Sidebar() {
               { name: '⚗️ Fórmulas', url: '#', key: 'cadastros-formulas', onClick: () => nav.navigate('cadastros-formulas') },
               { name: '🏭 Fornecedores', url: '#', key: 'cadastros-fornecedores', onClick: () => nav.navigate('cadastros-fornecedores') },
               { name: '📦 Itens', url: '#', key: 'cadastros-itens', onClick: () => nav.navigate('cadastros-itens') },
-              { name: '👤 Usuários', url: '#', key: 'cadastros-users', onClick: () => nav.navigate('cadastros-users') },
+              { name: '⚖️ Balanças', url: '#', key: 'balancas', onClick: () => nav.navigate('balancas') },
+              ...(can('ManageUsers') ? [{ name: '👤 Usuários', url: '#', key: 'cadastros-users', onClick: () => nav.navigate('cadastros-users') }] : []),
             ],
           },
         ]}
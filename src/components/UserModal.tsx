@@ -1,6 +1,6 @@
 import { useState } from 'react';
 import { Dialog, DialogType, DialogFooter, PrimaryButton, DefaultButton, TextField, ComboBox, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth } from '../api';
 
 interface Props {
   hidden: boolean;
@@ -18,11 +18,11 @@ export default function UserModal({ hidden, onDismiss, onSaved }: Props) {
     if (!username.trim() || !password) { alert('Preencha usuário e senha'); return; }
     try {
       setSaving(true);
-      await invoke('create_user', { username, password, role });
+      await invokeAuth('create_user', { username, password, role });
       onSaved();
       setUsername(''); setPassword(''); setRole('User');
       onDismiss();
-    } catch (e) { console.error(e); alert('Erro ao criar usuário'); } finally { setSaving(false); }
+    } catch (e) { console.error(e); alert('Erro ao criar usuário: ' + e); } finally { setSaving(false); }
   };
 
   return (
@@ -32,7 +32,7 @@ export default function UserModal({ hidden, onDismiss, onSaved }: Props) {
           <TextField label="Usuário" value={username} onChange={(_, v) => setUsername(v || '')} styles={{ root: { minWidth: 420 } }} />
           <TextField label="Senha" value={password} onChange={(_, v) => setPassword(v || '')} type="password" styles={{ root: { minWidth: 240 } }} />
         </Stack>
-        <ComboBox label="Role" options={[{key:'Admin', text:'Admin'},{key:'User', text:'User'}]} selectedKey={role} onChange={(_, o) => setRole(o?.key as string || 'User')} allowFreeform={false} />
+        <ComboBox label="Role" options={[{key:'Admin', text:'Admin'},{key:'Supervisor', text:'Supervisor'},{key:'Quality', text:'Qualidade'},{key:'User', text:'User'}]} selectedKey={role} onChange={(_, o) => setRole(o?.key as string || 'User')} allowFreeform={false} />
       </Stack>
       <DialogFooter>
         <PrimaryButton text="Salvar" onClick={handleSave} disabled={saving} />
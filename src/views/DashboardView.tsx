@@ -1,5 +1,5 @@
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, kgFromMg, Page, ProcessoStatus, processoStatusLabels } from '../api';
 import { PrimaryButton, Stack, DefaultButton } from '@fluentui/react';
 import { useNavigation } from '../NavigationContext';
 import './DashboardView.css';
@@ -7,7 +7,7 @@ import './DashboardView.css';
 interface Processo {
   id: string;
   nome: string;
-  status: string;
+  status: ProcessoStatus;
   weight: number;
   sprints: any[];
   formula: any;
@@ -26,9 +26,9 @@ export default function DashboardView() {
   const loadProcessos = async () => {
     try {
       setLoading(true);
-      const data = await invoke<Processo[]>('list_processos', { page: 0, pageSize: 100 });
-      // Filtrar apenas processos ativos
-      const ativos = data.filter(p => p.status.toLowerCase() !== 'terminado' && p.status.toLowerCase() !== 'finalizado');
+      const data = (await invokeAuth<Page<Processo>>('list_processos', { page: 0, pageSize: 100, sortBy: 'created_at', direction: 'desc' })).items;
+      // Filtrar apenas processos não encerrados
+      const ativos = data.filter(p => p.status !== 'Finished' && p.status !== 'Cancelled');
       setProcessos(ativos);
     } catch (error) {
       console.error('Erro ao carregar processos:', error);
@@ -45,7 +45,7 @@ export default function DashboardView() {
         const itemFormula = processo.formula?.itens?.find((fi: any) => fi.item.id === item.item.id);
         const baseWeight = itemFormula?.peso || 0;
         // Erro = actual - base_weight (NÃO usar target!)
-        const erro_item = (item.actual || 0) - baseWeight;
+        const erro_item = kgFromMg(item.actual || 0) - baseWeight;
         erro += erro_item;
       }
     }
@@ -55,12 +55,8 @@ export default function DashboardView() {
   const handleIniciarSprint = async (processo: Processo) => {
     try {
       setCreatingSprintFor(processo.id);
-      // Criar sprint automaticamente (sprints infinitos, sempre 1 remaining)
-      const sprint = await invoke<any>('create_sprint_for_processo', {
-        processoId: processo.id,
-        remainingSprints: 1,
-        operadorUsername: 'admin'
-      });
+      // Criar sprint automaticamente; os sprints restantes vêm do plano do processo
+      const sprint = await invokeAuth<any>('create_sprint_for_processo', { processoId: processo.id });
       
       // Navegar direto para execução com payload do sprint criado
       navigate('execucao-sprint', {
@@ -109,7 +105,7 @@ export default function DashboardView() {
                 <div className="processo-card-header">
                   <h3>📦 {processo.nome}</h3>
                   <span className={`status-badge ${processo.status.toLowerCase()}`}>
-                    {processo.status}
+                    {processoStatusLabels[processo.status] ?? processo.status}
                   </span>
                 </div>
 
@@ -141,7 +137,7 @@ export default function DashboardView() {
                   <PrimaryButton
                     text={creatingSprintFor === processo.id ? '⏳ Criando sprint...' : `▶️ Iniciar Sprint #${proximoSprint}`}
                     onClick={() => handleIniciarSprint(processo)}
-                    disabled={creatingSprintFor === processo.id}
+                    disabled={creatingSprintFor === processo.id || processo.status === 'Paused'}
                     styles={{
                       root: {
                         width: '100%',
@@ -1,5 +1,5 @@
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, Page } from '../api';
 import { TextField, PrimaryButton, DetailsList, IColumn, Stack, DefaultButton } from '@fluentui/react';
 import { useNavigation } from '../NavigationContext';
 
@@ -7,6 +7,9 @@ export default function ItensView() {
   const [query, setQuery] = useState('');
   const [itens, setItens] = useState<any[]>([]);
   const [page, setPage] = useState(0);
+  const [total, setTotal] = useState(0);
+  // cursor devolvido por cada página da listagem, usado para buscar a seguinte
+  const [cursors, setCursors] = useState<Record<number, string | null>>({});
   const { navigate } = useNavigation();
 
   useEffect(() => { load(); }, [page]);
@@ -14,18 +17,21 @@ export default function ItensView() {
   const load = async () => {
     try {
       if (query.trim()) {
-        const res = await invoke<any[]>('search_itens', { name: query, page, pageSize: 10 });
-        setItens(res);
+        const res = await invokeAuth<Page<any>>('search_itens', { name: query, page, pageSize: 10 });
+        setItens(res.items);
+        setTotal(res.total);
       } else {
-        const res = await invoke<any[]>('list_itens', { page, pageSize: 10 });
-        setItens(res);
+        const res = await invokeAuth<Page<any>>('list_itens', { page, pageSize: 10, sortBy: 'name', cursor: cursors[page] ?? null });
+        setItens(res.items);
+        setTotal(res.total);
+        setCursors(c => ({ ...c, [page + 1]: res.cursor }));
       }
     } catch (e) { console.error(e); }
   };
 
   const columns: IColumn[] = [
     { key: 'nome', name: 'Nome', fieldName: 'nome', minWidth: 150, isResizable: true },
-    { key: 'fornecedor', name: 'Fornecedor', fieldName: 'fornecedor', minWidth: 150, isResizable: true, onRender: (i: any) => i.fornecedor.nome }
+    { key: 'fornecedor', name: 'Fornecedor', fieldName: 'fornecedor', minWidth: 150, isResizable: true, onRender: (i: any) => i.fornecedor?.nome }
   ];
 
   return (
@@ -34,13 +40,14 @@ export default function ItensView() {
       <Stack tokens={{ childrenGap: 12 }}>
         <Stack horizontal tokens={{ childrenGap: 8 }}>
           <TextField placeholder="Pesquisar" value={query} onChange={(_, v) => setQuery(v || '')} />
-          <PrimaryButton text="Buscar" onClick={() => { setPage(0); load(); }} />
+          <PrimaryButton text="Buscar" onClick={() => { setPage(0); setCursors({}); load(); }} />
           <DefaultButton text="Novo Item" onClick={() => navigate('novo-item')} />
         </Stack>
         <DetailsList items={itens} columns={columns} selectionMode={0} />
         <Stack horizontal tokens={{ childrenGap: 8 }}>
           <DefaultButton text="Anterior" onClick={() => { if (page>0) setPage(page-1); }} />
-          <DefaultButton text="Próxima" onClick={() => setPage(page+1)} />
+          <DefaultButton text="Próxima" disabled={(page + 1) * 10 >= total} onClick={() => setPage(page+1)} />
+          <span>{total} registro(s)</span>
         </Stack>
       </Stack>
     </div>
@@ -0,0 +1,57 @@
+import { useState } from 'react';
+import { TextField, PrimaryButton, Stack, MessageBar, MessageBarType } from '@fluentui/react';
+import { useSession } from '../SessionContext';
+
+export default function LoginView() {
+  const { token, mustChangePassword, login, changePassword } = useSession();
+  const [username, setUsername] = useState('');
+  const [password, setPassword] = useState('');
+  const [newPassword, setNewPassword] = useState('');
+  const [confirmPassword, setConfirmPassword] = useState('');
+  const [error, setError] = useState('');
+  const [busy, setBusy] = useState(false);
+
+  const handleLogin = async () => {
+    if (!username.trim() || !password) { setError('Preencha usuário e senha'); return; }
+    try {
+      setBusy(true);
+      setError('');
+      await login(username, password);
+    } catch (e) { setError(String(e)); } finally { setBusy(false); }
+  };
+
+  const handleChangePassword = async () => {
+    if (newPassword !== confirmPassword) { setError('As senhas não conferem'); return; }
+    try {
+      setBusy(true);
+      setError('');
+      await changePassword(password, newPassword);
+      setPassword(''); setNewPassword(''); setConfirmPassword('');
+    } catch (e) { setError(String(e)); } finally { setBusy(false); }
+  };
+
+  const changing = token !== null && mustChangePassword;
+
+  return (
+    <div className="view-container" style={{ maxWidth: 420, margin: '10vh auto' }}>
+      <h2>{changing ? 'Troca de senha obrigatória' : 'Entrar'}</h2>
+      <Stack tokens={{ childrenGap: 12 }}>
+        {error && <MessageBar messageBarType={MessageBarType.error}>{error}</MessageBar>}
+        {changing ? (
+          <>
+            <p>Defina uma nova senha antes de continuar.</p>
+            <TextField label="Nova senha" type="password" value={newPassword} onChange={(_, v) => setNewPassword(v || '')} />
+            <TextField label="Confirmar nova senha" type="password" value={confirmPassword} onChange={(_, v) => setConfirmPassword(v || '')} />
+            <PrimaryButton text="Salvar senha" onClick={handleChangePassword} disabled={busy} />
+          </>
+        ) : (
+          <>
+            <TextField label="Usuário" value={username} onChange={(_, v) => setUsername(v || '')} />
+            <TextField label="Senha" type="password" value={password} onChange={(_, v) => setPassword(v || '')} onKeyPress={(e) => { if (e.key === 'Enter') handleLogin(); }} />
+            <PrimaryButton text="Entrar" onClick={handleLogin} disabled={busy} />
+          </>
+        )}
+      </Stack>
+    </div>
+  );
+}
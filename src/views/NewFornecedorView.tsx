@@ -1,6 +1,6 @@
 import { useState } from 'react';
 import { TextField, PrimaryButton, DefaultButton, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth } from '../api';
 import { useNavigation } from '../NavigationContext';
 
 export default function NewFornecedorView() {
@@ -12,7 +12,7 @@ export default function NewFornecedorView() {
     if (!nome.trim()) { alert('Nome é obrigatório'); return; }
     try {
       setSaving(true);
-      await invoke('create_fornecedor', { nome });
+      await invokeAuth('create_fornecedor', { nome });
       // fallback: voltar para lista de fornecedores
       navigate('cadastros-fornecedores');
     } catch (e) { console.error(e); alert('Erro ao criar fornecedor'); } finally { setSaving(false); }
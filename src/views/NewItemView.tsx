@@ -1,12 +1,15 @@
 import { useState, useEffect } from 'react';
-import { TextField, PrimaryButton, DefaultButton, Stack, ComboBox, IComboBoxOption } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { TextField, PrimaryButton, DefaultButton, Stack, ComboBox, IComboBoxOption, Dropdown } from '@fluentui/react';
+import { invokeAuth, mgFromKg, Page, Unit, unitOptions, unitNeedsDensity } from '../api';
 import { useNavigation } from '../NavigationContext';
 
 export default function NewItemView() {
   const [nome, setNome] = useState('');
   const [fornecedores, setFornecedores] = useState<IComboBoxOption[]>([]);
   const [fornecedorId, setFornecedorId] = useState('');
+  const [unit, setUnit] = useState<Unit>('kg');
+  const [density, setDensity] = useState('');
+  const [resolution, setResolution] = useState('');
   const [saving, setSaving] = useState(false);
   const { navigate } = useNavigation();
 
@@ -14,7 +17,7 @@ export default function NewItemView() {
 
   const loadFornecedores = async () => {
     try {
-      const res = await invoke<any[]>('list_fornecedores', { page: 0, pageSize: 200 });
+      const res = (await invokeAuth<Page<any>>('list_fornecedores', { page: 0, pageSize: 200, sortBy: 'name' })).items;
       const opts = res.map(f => ({ key: f.id, text: f.nome }));
       setFornecedores(opts);
       if (opts.length > 0) setFornecedorId(opts[0].key as string);
@@ -24,9 +27,12 @@ export default function NewItemView() {
   const handleSave = async () => {
     if (!nome.trim()) { alert('Nome é obrigatório'); return; }
     if (!fornecedorId) { alert('Selecione um fornecedor'); return; }
+    if (unitNeedsDensity(unit) && !(parseFloat(density) > 0)) { alert('Informe a densidade do item'); return; }
     try {
       setSaving(true);
-      await invoke('create_item', { nome, fornecedorId });
+      // campos em branco = não informado
+      const optional = (v: string) => v.trim() ? parseFloat(v) : null;
+      await invokeAuth('create_item', { nome, fornecedorId, measure: { unit, density: optional(density), resolution: resolution.trim() ? mgFromKg(parseFloat(resolution)) : null } });
       navigate('cadastros-itens');
     } catch (e) { console.error(e); alert('Erro ao criar item'); } finally { setSaving(false); }
   };
@@ -39,6 +45,18 @@ export default function NewItemView() {
           <TextField label="Nome" value={nome} onChange={(_, v) => setNome(v || '')} styles={{ root: { minWidth: 420 } }} />
           <ComboBox label="Fornecedor" options={fornecedores} selectedKey={fornecedorId} onChange={(_, o) => setFornecedorId(o?.key as string || '')} allowFreeform autoComplete="on" styles={{ root: { minWidth: 320 } }} />
         </Stack>
+        <Stack horizontal tokens={{ childrenGap: 12 }}>
+          <Dropdown label="Unidade" options={unitOptions} selectedKey={unit} onChange={(_, o) => setUnit(o?.key as Unit)} styles={{ root: { width: 120 } }} />
+          {unitNeedsDensity(unit) && (
+            <TextField
+              label={unit === 'un' ? 'Massa por unidade (kg)' : 'Densidade (kg/L)'}
+              value={density}
+              onChange={(_, v) => setDensity(v || '')}
+              styles={{ root: { width: 200 } }}
+            />
+          )}
+          <TextField label="Divisão da balança (kg)" value={resolution} onChange={(_, v) => setResolution(v || '')} placeholder="Opcional" styles={{ root: { width: 200 } }} />
+        </Stack>
         <Stack horizontal tokens={{ childrenGap: 8 }}>
           <PrimaryButton text="Criar" onClick={handleSave} disabled={saving} />
           <DefaultButton text="Cancelar" onClick={() => navigate('cadastros-itens')} />
@@ -1,12 +1,18 @@
 import { useState, useEffect } from 'react';
 import { TextField, PrimaryButton, DefaultButton, Stack, ComboBox } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, mgFromKg, Page, CompensationStrategy } from '../api';
+import CompensationPicker from '../components/CompensationPicker';
 import { useNavigation } from '../NavigationContext';
 
 export default function NewProcessView() {
   const [nome, setNome] = useState('');
   const [formulas, setFormulas] = useState<any[]>([]);
   const [formulaId, setFormulaId] = useState('');
+  const [compensation, setCompensation] = useState<CompensationStrategy>({ kind: 'full_carry' });
+  const [mixerCapacity, setMixerCapacity] = useState('');
+  const [targetMass, setTargetMass] = useState('');
+  const [plannedSprints, setPlannedSprints] = useState('');
+  const [resolution, setResolution] = useState('');
   const [saving, setSaving] = useState(false);
   const { navigate } = useNavigation();
 
@@ -14,7 +20,7 @@ export default function NewProcessView() {
 
   const loadFormulas = async () => {
     try {
-      const res = await invoke<any[]>('list_formulas', { page: 0, pageSize: 200 });
+      const res = (await invokeAuth<Page<any>>('list_formulas', { page: 0, pageSize: 200, sortBy: 'name' })).items;
       setFormulas(res);
       if (res.length > 0) setFormulaId(res[0].id);
     } catch (e) { console.error(e); }
@@ -25,7 +31,19 @@ export default function NewProcessView() {
     if (!formulaId) { alert('Selecione uma fórmula'); return; }
     try {
       setSaving(true);
-      await invoke('create_processo', { nome, formulaId });
+      // campos em branco = sem limite / sem planejamento
+      const optional = (v: string, parse: (v: string) => number) => v.trim() ? parse(v) : null;
+      await invokeAuth('create_processo', {
+        nome,
+        formulaId,
+        settings: {
+          compensation,
+          mixer_capacity: optional(mixerCapacity, parseFloat),
+          target_mass: optional(targetMass, parseFloat),
+          planned_sprints: optional(plannedSprints, v => parseInt(v, 10)),
+          resolution: optional(resolution, v => mgFromKg(parseFloat(v))),
+        }
+      });
       navigate('processos');
     } catch (e) { console.error(e); alert('Erro ao criar processo'); } finally { setSaving(false); }
   };
@@ -38,8 +56,14 @@ export default function NewProcessView() {
           <TextField label="Nome do Processo" value={nome} onChange={(_, v) => setNome(v || '')} styles={{ root: { minWidth: 420 } }} />
           <ComboBox label="Fórmula" options={formulas.map(f => ({ key: f.id, text: f.nome }))} selectedKey={formulaId} onChange={(_, o) => setFormulaId(o?.key as string || '')} allowFreeform={false} styles={{ root: { minWidth: 320 } }} />
         </Stack>
+        <Stack horizontal tokens={{ childrenGap: 12 }}>
+          <TextField label="Massa total planejada (kg)" value={targetMass} onChange={(_, v) => setTargetMass(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+          <TextField label="Nº de sprints planejados" value={plannedSprints} onChange={(_, v) => setPlannedSprints(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+          <TextField label="Divisão da balança (kg)" value={resolution} onChange={(_, v) => setResolution(v || '')} placeholder="Opcional" styles={{ root: { width: 220 } }} />
+        </Stack>
+        <CompensationPicker value={compensation} onChange={setCompensation} mixerCapacity={mixerCapacity} onMixerCapacityChange={setMixerCapacity} />
         <div style={{ color: '#605e5c', fontSize: 13 }}>
-          Preencha o nome e escolha a fórmula. O peso total do processo será calculado automaticamente a partir dos itens da fórmula.
+          Preencha o nome e escolha a fórmula. Informe a massa total e/ou o número de sprints para planejar o lote; sem eles, cada sprint segue os pesos da fórmula.
         </div>
         <Stack horizontal tokens={{ childrenGap: 8 }}>
           <PrimaryButton text="Criar Processo" onClick={handleSave} disabled={saving} />
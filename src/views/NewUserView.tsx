@@ -1,6 +1,6 @@
 import { useState } from 'react';
 import { TextField, PrimaryButton, DefaultButton, ComboBox, Stack } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth } from '../api';
 import { useNavigation } from '../NavigationContext';
 
 export default function NewUserView() {
@@ -14,9 +14,9 @@ export default function NewUserView() {
     if (!username.trim() || !password) { alert('Preencha usuário e senha'); return; }
     try {
       setSaving(true);
-      await invoke('create_user', { username, password, role });
+      await invokeAuth('create_user', { username, password, role });
       navigate('cadastros-users');
-    } catch (e) { console.error(e); alert('Erro ao criar usuário'); } finally { setSaving(false); }
+    } catch (e) { console.error(e); alert('Erro ao criar usuário: ' + e); } finally { setSaving(false); }
   };
 
   return (
@@ -27,7 +27,7 @@ export default function NewUserView() {
           <TextField label="Usuário" value={username} onChange={(_, v) => setUsername(v || '')} styles={{ root: { minWidth: 420 } }} />
           <TextField label="Senha" value={password} onChange={(_, v) => setPassword(v || '')} type="password" styles={{ root: { minWidth: 240 } }} />
         </Stack>
-        <ComboBox label="Role" options={[{key:'Admin', text:'Admin'},{key:'User', text:'User'}]} selectedKey={role} onChange={(_, o) => setRole(o?.key as string || 'User')} allowFreeform={false} />
+        <ComboBox label="Role" options={[{key:'Admin', text:'Admin'},{key:'Supervisor', text:'Supervisor'},{key:'Quality', text:'Qualidade'},{key:'User', text:'User'}]} selectedKey={role} onChange={(_, o) => setRole(o?.key as string || 'User')} allowFreeform={false} />
         <Stack horizontal tokens={{ childrenGap: 8 }}>
           <PrimaryButton text="Criar" onClick={handleSave} disabled={saving} />
           <DefaultButton text="Cancelar" onClick={() => navigate('cadastros-users')} />
@@ -1,23 +1,51 @@
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, kgFromMg, ProcessoStatus, processoStatusLabels, isProcessoAtivo } from '../api';
 import { DefaultButton, Stack } from '@fluentui/react';
 import { useNavigation } from '../NavigationContext';
+import { useSession } from '../SessionContext';
 
 interface Processo {
   id: string;
   nome: string;
-  status: string;
+  status: ProcessoStatus;
   weight: number;
   sprints: Sprint[];
+  plan: { target_mass: number; planned_sprints: number; scale: number } | null;
+  progress: {
+    sprints_done: number;
+    planned_sprints: number | null;
+    mass_produced: number;
+    target_mass: number | null;
+    complete: boolean;
+  };
   formula: {
     itens: Array<{ item: { id: string; nome: string }; peso: number }>;
   };
+  reconciliation: Reconciliation | null;
+}
+
+interface Reconciliation {
+  sprints_completed: number;
+  lines: Array<{
+    item_id: string;
+    item_nome: string;
+    theoretical: number;
+    actual: number;
+    residual: number;
+    expected_pct: number;
+    actual_pct: number;
+  }>;
+  total_theoretical: number;
+  total_actual: number;
+  generated_at: string;
+  sign_off: { username: string; comment: string | null; created_at: string } | null;
 }
 
 interface Sprint {
   id: string;
   numero: number;
   itens: SprintItem[];
+  status: string;
   created_at: string;
 }
 
@@ -37,10 +65,12 @@ interface ChartDataPoint {
 
 export default function ProcessoDashboardView() {
   const { payload, navigate } = useNavigation();
+  const { can } = useSession();
   const processoId = payload?.processoId as string;
   const [processo, setProcesso] = useState<Processo | null>(null);
   const [loading, setLoading] = useState(true);
   const [chartData, setChartData] = useState<ChartDataPoint[]>([]);
+  const [openSprint, setOpenSprint] = useState<Sprint | null>(null);
 
   useEffect(() => {
     if (processoId) {
@@ -51,11 +81,12 @@ export default function ProcessoDashboardView() {
   const loadProcesso = async () => {
     try {
       setLoading(true);
-      const data = await invoke<Processo>('get_processo', { id: processoId });
+      const data = await invokeAuth<Processo>('get_processo', { id: processoId });
       if (data) {
         setProcesso(data);
         calculateChartData(data);
       }
+      setOpenSprint(await invokeAuth<Sprint | null>('get_open_sprint', { processoId }));
     } catch (error) {
       console.error('Erro ao carregar processo:', error);
       alert('Erro ao carregar processo: ' + error);
@@ -74,13 +105,14 @@ export default function ProcessoDashboardView() {
       itemColors[fi.item.id] = colors[idx % colors.length];
     });
     
-    for (const sprint of proc.sprints) {
+    // Sprints abortados ou anulados não entram no histórico de desvios
+    for (const sprint of proc.sprints.filter(s => s.status === 'Completed')) {
       for (const item of sprint.itens) {
         // Busca peso base na fórmula
         const formulaItem = proc.formula.itens.find(fi => fi.item.id === item.item.id);
-        const baseWeight = formulaItem?.peso || 0;
+        const baseWeight = (formulaItem?.peso || 0) * (proc.plan?.scale ?? 1);
         // Desvio = actual - base_weight
-        const actual = item.actual || 0;
+        const actual = kgFromMg(item.actual || 0);
         const desvio = actual - baseWeight;
         
         data.push({
@@ -100,11 +132,7 @@ export default function ProcessoDashboardView() {
     if (!processo) return;
     
     try {
-      const sprint = await invoke<any>('create_sprint_for_processo', {
-        processoId: processo.id,
-        remainingSprints: 1,
-        operadorUsername: 'admin'
-      });
+      const sprint = await invokeAuth<any>('create_sprint_for_processo', { processoId: processo.id });
       
       navigate('execucao-sprint', {
         processoId: processo.id,
@@ -126,23 +154,38 @@ export default function ProcessoDashboardView() {
     }
 
     try {
-      await invoke('finalize_processo', { processoId: processo.id });
-      navigate('processos');
+      await invokeAuth('finalize_processo', { processoId: processo.id });
+      // o fechamento do lote é gerado na finalização
+      await loadProcesso();
     } catch (error) {
       console.error('Erro ao finalizar processo:', error);
       alert('Erro ao finalizar processo: ' + error);
     }
   };
 
+  const handleSignOff = async () => {
+    if (!processo) return;
+    const comment = prompt('Observação da qualidade (opcional):');
+    if (comment === null) return;
+
+    try {
+      await invokeAuth('sign_off_processo', { processoId: processo.id, comment });
+      await loadProcesso();
+    } catch (error) {
+      console.error('Erro ao aprovar lote:', error);
+      alert('Erro ao aprovar lote: ' + error);
+    }
+  };
+
   const calcularErroAcumulado = (): number => {
     if (!processo) return 0;
     
     let erro = 0;
-    for (const sprint of processo.sprints) {
+    for (const sprint of processo.sprints.filter(s => s.status === 'Completed')) {
       for (const item of sprint.itens) {
         const formulaItem = processo.formula.itens.find(fi => fi.item.id === item.item.id);
-        const baseWeight = formulaItem?.peso || 0;
-        const actual = item.actual || 0;
+        const baseWeight = (formulaItem?.peso || 0) * (processo.plan?.scale ?? 1);
+        const actual = kgFromMg(item.actual || 0);
         erro += (actual - baseWeight);
       }
     }
@@ -196,12 +239,13 @@ export default function ProcessoDashboardView() {
           <div style={{ color: 'white' }}>
             <h2 style={{ margin: 0, marginBottom: 4 }}>📊 {processo.nome}</h2>
             <div style={{ fontSize: 14, opacity: 0.9 }}>
-              Sprint #{processo.sprints.length + 1} • {processo.formula.itens.length} itens
+              Sprint #{processo.sprints.length + 1} • {processo.formula.itens.length} itens • {processoStatusLabels[processo.status] ?? processo.status}
             </div>
           </div>
           <div style={{ display: 'flex', gap: 12, alignItems: 'center' }}>
             <button
               onClick={handleNovoSprint}
+              disabled={!isProcessoAtivo(processo.status)}
               style={{
                 backgroundColor: '#107c10',
                 color: 'white',
@@ -226,7 +270,7 @@ export default function ProcessoDashboardView() {
                 e.currentTarget.style.transform = 'scale(1)';
               }}
             >
-              ▶️ NOVO SPRINT
+              {openSprint ? `⏯️ RETOMAR SPRINT #${openSprint.numero}` : '▶️ NOVO SPRINT'}
             </button>
             <DefaultButton 
               text="← Voltar" 
@@ -289,6 +333,25 @@ export default function ProcessoDashboardView() {
           <div style={{ fontSize: 12, color: '#605e5c', marginBottom: 4 }}>Próximo Sprint</div>
           <div style={{ fontSize: 24, fontWeight: 600 }}>#{processo.sprints.length + 1}</div>
         </div>
+
+        {processo.progress.planned_sprints !== null && (
+          <div style={{ 
+            padding: 20, 
+            backgroundColor: processo.progress.complete ? '#dff6dd' : '#f3f2f1', 
+            borderRadius: 4,
+            border: '1px solid #edebe9'
+          }}>
+            <div style={{ fontSize: 12, color: '#605e5c', marginBottom: 4 }}>
+              {processo.progress.complete ? '✅ Planejamento concluído' : 'Andamento do Lote'}
+            </div>
+            <div style={{ fontSize: 24, fontWeight: 600 }}>
+              {processo.progress.sprints_done}/{processo.progress.planned_sprints} sprints
+            </div>
+            <div style={{ fontSize: 13, color: '#605e5c' }}>
+              {processo.progress.mass_produced.toFixed(2)} / {(processo.progress.target_mass ?? 0).toFixed(2)} kg
+            </div>
+          </div>
+        )}
       </div>
 
       {/* Gráfico de Desvios por Item */}
@@ -435,11 +498,69 @@ export default function ProcessoDashboardView() {
         </table>
       </div>
 
+      {/* Fechamento do Lote */}
+      {processo.reconciliation && (
+        <div style={{ marginBottom: 32 }}>
+          <h3>🧾 Fechamento do Lote</h3>
+          <div style={{ fontSize: 13, color: '#605e5c', marginBottom: 8 }}>
+            {processo.reconciliation.sprints_completed} sprints concluídos • gerado em {new Date(processo.reconciliation.generated_at).toLocaleString('pt-BR')}
+          </div>
+          <table style={{ width: '100%', borderCollapse: 'collapse' }}>
+            <thead>
+              <tr style={{ backgroundColor: '#e1dfdd' }}>
+                <th style={{ padding: 12, textAlign: 'left', border: '1px solid #c8c6c4' }}>Item</th>
+                <th style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>Teórico (kg)</th>
+                <th style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>Dosado (kg)</th>
+                <th style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>Resíduo (kg)</th>
+                <th style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>Fórmula (%)</th>
+                <th style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>Real (%)</th>
+              </tr>
+            </thead>
+            <tbody>
+              {processo.reconciliation.lines.map((line, idx) => (
+                <tr key={line.item_id} style={{ backgroundColor: idx % 2 === 0 ? '#ffffff' : '#f3f2f1' }}>
+                  <td style={{ padding: 12, border: '1px solid #edebe9' }}>{line.item_nome}</td>
+                  <td style={{ padding: 12, textAlign: 'right', border: '1px solid #edebe9' }}>{kgFromMg(line.theoretical).toFixed(2)}</td>
+                  <td style={{ padding: 12, textAlign: 'right', border: '1px solid #edebe9' }}>{kgFromMg(line.actual).toFixed(2)}</td>
+                  <td style={{ padding: 12, textAlign: 'right', border: '1px solid #edebe9', fontWeight: 600, color: Math.abs(kgFromMg(line.residual)) < 0.005 ? '#107c10' : '#d13438' }}>
+                    {line.residual >= 0 ? '+' : ''}{kgFromMg(line.residual).toFixed(2)}
+                  </td>
+                  <td style={{ padding: 12, textAlign: 'right', border: '1px solid #edebe9' }}>{line.expected_pct.toFixed(2)}</td>
+                  <td style={{ padding: 12, textAlign: 'right', border: '1px solid #edebe9' }}>{line.actual_pct.toFixed(2)}</td>
+                </tr>
+              ))}
+              <tr style={{ backgroundColor: '#e1dfdd', fontWeight: 600 }}>
+                <td style={{ padding: 12, border: '1px solid #c8c6c4' }}>Total</td>
+                <td style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>{kgFromMg(processo.reconciliation.total_theoretical).toFixed(2)}</td>
+                <td style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>{kgFromMg(processo.reconciliation.total_actual).toFixed(2)}</td>
+                <td style={{ padding: 12, textAlign: 'right', border: '1px solid #c8c6c4' }}>
+                  {kgFromMg(processo.reconciliation.total_actual - processo.reconciliation.total_theoretical).toFixed(2)}
+                </td>
+                <td style={{ padding: 12, border: '1px solid #c8c6c4' }} colSpan={2} />
+              </tr>
+            </tbody>
+          </table>
+          <div style={{ marginTop: 12 }}>
+            {processo.reconciliation.sign_off ? (
+              <span style={{ color: '#107c10', fontWeight: 600 }}>
+                ✅ Aprovado pela qualidade: {processo.reconciliation.sign_off.username} em {new Date(processo.reconciliation.sign_off.created_at).toLocaleString('pt-BR')}
+                {processo.reconciliation.sign_off.comment && ` — ${processo.reconciliation.sign_off.comment}`}
+              </span>
+            ) : can('SignOffBatches') ? (
+              <DefaultButton text="✔️ Aprovar Lote" onClick={handleSignOff} styles={{ root: { height: 40 } }} />
+            ) : (
+              <span style={{ color: '#605e5c' }}>Aguardando aprovação da qualidade</span>
+            )}
+          </div>
+        </div>
+      )}
+
       {/* Ações Secundárias */}
       <Stack horizontal tokens={{ childrenGap: 12 }}>
         <DefaultButton
           text="✅ Finalizar Processo"
           onClick={handleFinalizar}
+          disabled={processo.status !== 'InProgress'}
           styles={{ root: { height: 40 } }}
         />
         <DefaultButton
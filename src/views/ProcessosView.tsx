@@ -1,5 +1,5 @@
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, Page, ProcessoStatus, processoStatusLabels, isProcessoAtivo } from '../api';
 import { DetailsList, IColumn, PrimaryButton, DefaultButton, Stack } from '@fluentui/react';
 import SprintExecutionView from './SprintExecutionView';
 import { useNavigation } from '../NavigationContext';
@@ -51,7 +51,7 @@ export default function ProcessosView() {
   const loadProcessos = async () => {
     try {
       setLoading(true);
-      const data = await invoke<Processo[]>('list_processos', { page: 0, pageSize: 100 });
+      const data = (await invokeAuth<Page<Processo>>('list_processos', { page: 0, pageSize: 100, sortBy: 'created_at', direction: 'desc' })).items;
       setProcessos(data);
     } catch (error) {
       console.error('Erro ao carregar processos:', error);
@@ -60,17 +60,29 @@ export default function ProcessosView() {
     }
   };
 
-  const isProcessoTerminado = (status: string) => {
-    return status.toLowerCase() === 'terminado' || status.toLowerCase() === 'finalizado';
+  const isProcessoEncerrado = (status: ProcessoStatus) => status === 'Finished' || status === 'Cancelled';
+
+  // Pausar e cancelar pedem motivo; retomar aceita um comentário opcional
+  const handleChangeStatus = async (processo: Processo, command: 'pause_processo' | 'resume_processo' | 'cancel_processo') => {
+    const acao = { pause_processo: 'pausar', resume_processo: 'retomar', cancel_processo: 'cancelar' }[command];
+    const reason = prompt(`Motivo para ${acao} o processo "${processo.nome}":`);
+    if (reason === null) return;
+    if (command !== 'resume_processo' && !reason.trim()) {
+      alert('Informe o motivo');
+      return;
+    }
+    try {
+      await invokeAuth(command, { processoId: processo.id, reason: reason || null });
+      loadProcessos();
+    } catch (error) {
+      console.error(`Erro ao ${acao} processo:`, error);
+      alert(`Erro ao ${acao} processo: ` + error);
+    }
   };
 
   const handleAddSprint = async (processo: Processo) => {
     try {
-      const sprint = await invoke<any>('create_sprint_for_processo', {
-        processoId: processo.id,
-        remainingSprints: 1,
-        operadorUsername: 'admin'
-      });
+      const sprint = await invokeAuth<any>('create_sprint_for_processo', { processoId: processo.id });
       navigate('execucao-sprint', {
         processoId: processo.id,
         processoNome: processo.nome,
@@ -90,7 +102,7 @@ export default function ProcessosView() {
 
     try {
       // Salva sprint no processo
-      await invoke('save_sprint_to_processo', {
+      await invokeAuth('save_sprint_to_processo', {
         processoId: selectedProcesso.id,
         sprint: currentSprint
       });
@@ -118,7 +130,7 @@ export default function ProcessosView() {
     }
 
     try {
-      await invoke('finalize_processo', { processoId: processo.id });
+      await invokeAuth('finalize_processo', { processoId: processo.id });
       await loadProcessos();
     } catch (error) {
       console.error('Erro ao finalizar processo:', error);
@@ -132,7 +144,7 @@ export default function ProcessosView() {
     }
 
     try {
-      await invoke('delete_processo', { processoId: processo.id });
+      await invokeAuth('delete_processo', { processoId: processo.id });
       await loadProcessos();
     } catch (error) {
       console.error('Erro ao deletar processo:', error);
@@ -160,6 +172,7 @@ export default function ProcessosView() {
       minWidth: 100,
       maxWidth: 150,
       isResizable: true,
+      onRender: (item: Processo) => <span>{processoStatusLabels[item.status] ?? item.status}</span>
     },
     {
       key: 'weight',
@@ -188,35 +201,56 @@ export default function ProcessosView() {
         <Stack horizontal tokens={{ childrenGap: 8 }} wrap>
           <PrimaryButton
             text="➕ Sprint"
-            disabled={isProcessoTerminado(item.status)}
+            disabled={!isProcessoAtivo(item.status)}
             styles={{
               root: {
-                backgroundColor: isProcessoTerminado(item.status) ? '#ccc' : '#0078d4',
+                backgroundColor: !isProcessoAtivo(item.status) ? '#ccc' : '#0078d4',
                 border: 'none',
                 height: '32px'
               },
               rootHovered: {
-                backgroundColor: isProcessoTerminado(item.status) ? '#ccc' : '#106ebe',
+                backgroundColor: !isProcessoAtivo(item.status) ? '#ccc' : '#106ebe',
               }
             }}
             onClick={() => handleAddSprint(item)}
           />
-          {!isProcessoTerminado(item.status) && (
+          {!isProcessoEncerrado(item.status) && (
             <>
-              <DefaultButton
-                text="✅ Finalizar"
-                onClick={() => handleFinalizeProcesso(item)}
-                styles={{ root: { height: '32px' } }}
-              />
-              {item.sprints.length > 0 && (
+              {item.status === 'InProgress' && (
+                <>
+                  <DefaultButton
+                    text="✅ Finalizar"
+                    onClick={() => handleFinalizeProcesso(item)}
+                    styles={{ root: { height: '32px' } }}
+                  />
+                  <DefaultButton
+                    text="⏸️ Pausar"
+                    onClick={() => handleChangeStatus(item, 'pause_processo')}
+                    styles={{ root: { height: '32px' } }}
+                  />
+                </>
+              )}
+              {item.status === 'Paused' && (
                 <DefaultButton
-                  text="📊 Dashboard"
-                  onClick={() => handleViewDashboard(item)}
+                  text="▶️ Retomar"
+                  onClick={() => handleChangeStatus(item, 'resume_processo')}
                   styles={{ root: { height: '32px' } }}
                 />
               )}
+              <DefaultButton
+                text="🚫 Cancelar"
+                onClick={() => handleChangeStatus(item, 'cancel_processo')}
+                styles={{ root: { height: '32px' } }}
+              />
             </>
           )}
+          {item.sprints.length > 0 && (
+            <DefaultButton
+              text="📊 Dashboard"
+              onClick={() => handleViewDashboard(item)}
+              styles={{ root: { height: '32px' } }}
+            />
+          )}
           <DefaultButton
             text="🗑️ Deletar"
             onClick={() => handleDeleteProcesso(item)}
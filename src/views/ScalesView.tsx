@@ -0,0 +1,185 @@
+import { useEffect, useState } from 'react';
+import { Checkbox, DefaultButton, Dropdown, PrimaryButton, Stack, TextField } from '@fluentui/react';
+import {
+  invokeAuth, kgFromMg, mgFromKg, listenScales, applyScaleEvent, describeTransport, healthColor, healthLabel,
+  ScaleProtocol, ScaleStatus, ScaleTransport, scaleProtocolOptions, SIMULATOR_ID,
+} from '../api';
+import { useSession } from '../SessionContext';
+
+const baudOptions = [2400, 4800, 9600, 19200, 38400].map(b => ({ key: b, text: String(b) }));
+const transportOptions = [
+  { key: 'serial', text: 'Serial / USB' },
+  { key: 'tcp', text: 'Rede (TCP)' },
+];
+
+export default function ScalesView() {
+  const [scales, setScales] = useState<ScaleStatus[]>([]);
+  const [id, setId] = useState('');
+  const [name, setName] = useState('');
+  const [kind, setKind] = useState<'serial' | 'tcp'>('serial');
+  const [serialPort, setSerialPort] = useState('');
+  const [baudRate, setBaudRate] = useState(9600);
+  const [host, setHost] = useState('');
+  const [tcpPort, setTcpPort] = useState('');
+  const [protocol, setProtocol] = useState<ScaleProtocol>('mtsics');
+  const [stableReadings, setStableReadings] = useState('3');
+  const [stableBand, setStableBand] = useState('');
+  const [simProtocol, setSimProtocol] = useState<ScaleProtocol>('mtsics');
+  const [simLoad, setSimLoad] = useState('');
+  const [simMoving, setSimMoving] = useState(false);
+  const [saving, setSaving] = useState(false);
+  const { can } = useSession();
+
+  const load = async () => {
+    try { setScales(await invokeAuth<ScaleStatus[]>('list_scales')); } catch (e) { console.error(e); }
+  };
+
+  useEffect(() => {
+    load();
+    const unlisten = listenScales(event => setScales(prev => applyScaleEvent(prev, event)));
+    return () => { unlisten.then(f => f()); };
+  }, []);
+
+  const resetForm = () => {
+    setId(''); setName(''); setSerialPort(''); setHost(''); setTcpPort('');
+  };
+
+  // Preenche o formulário para editar uma balança gravada
+  const edit = (s: ScaleStatus) => {
+    setId(s.config.id);
+    setName(s.config.name);
+    setProtocol(s.config.protocol);
+    setStableReadings(String(s.config.stable_readings));
+    setStableBand(s.config.stable_band ? String(kgFromMg(s.config.stable_band)) : '');
+    setKind(s.config.transport.kind);
+    if (s.config.transport.kind === 'serial') {
+      setSerialPort(s.config.transport.port);
+      setBaudRate(s.config.transport.baud_rate);
+    } else {
+      setHost(s.config.transport.host);
+      setTcpPort(String(s.config.transport.port));
+    }
+  };
+
+  const handleSave = async () => {
+    let transport: ScaleTransport;
+    if (kind === 'serial') {
+      if (!serialPort.trim()) { alert('Informe a porta serial'); return; }
+      transport = { kind: 'serial', port: serialPort.trim(), baud_rate: baudRate };
+    } else {
+      const port = parseInt(tcpPort);
+      if (!host.trim() || !(port > 0)) { alert('Informe o endereço e a porta do indicador'); return; }
+      transport = { kind: 'tcp', host: host.trim(), port };
+    }
+    try {
+      setSaving(true);
+      await invokeAuth('save_scale', {
+        config: {
+          id: id.trim() || crypto.randomUUID(),
+          name: name.trim(),
+          transport,
+          protocol,
+          stable_readings: parseInt(stableReadings) || 3,
+          stable_band: mgFromKg(parseFloat(stableBand) || 0),
+          poll_interval_ms: 200,
+        },
+      });
+      resetForm();
+      await load();
+    } catch (e) { alert('❌ ' + e); } finally { setSaving(false); }
+  };
+
+  const handleDelete = async (s: ScaleStatus) => {
+    if (!confirm(`Remover a balança "${s.config.name || s.config.id}" desta estação?`)) return;
+    try { await invokeAuth('delete_scale', { scaleId: s.config.id }); } catch (e) { alert('❌ ' + e); }
+    load();
+  };
+
+  const run = async (cmd: string, scaleId: string) => {
+    try { await invokeAuth(cmd, { scaleId }); } catch (e) { alert('❌ ' + e); }
+  };
+
+  const simulatorOn = scales.some(s => s.config.id === SIMULATOR_ID);
+
+  const toggleSimulator = async () => {
+    try {
+      if (simulatorOn) await invokeAuth('stop_scale_simulator');
+      else await invokeAuth('start_scale_simulator', { protocol: simProtocol });
+    } catch (e) { alert('❌ ' + e); }
+    load();
+  };
+
+  const applySimLoad = async () => {
+    const value = parseFloat(simLoad);
+    if (isNaN(value)) { alert('Peso inválido'); return; }
+    try { await invokeAuth('set_scale_simulator_load', { load: value, stable: !simMoving }); } catch (e) { alert('❌ ' + e); }
+  };
+
+  const manage = can('ManageScales');
+  const operate = can('OperateSprints');
+
+  return (
+    <div className="view-container">
+      <h2>Balanças</h2>
+      {manage && (
+        <Stack tokens={{ childrenGap: 8 }}>
+          <Stack horizontal tokens={{ childrenGap: 12 }} verticalAlign="end" styles={{ root: { flexWrap: 'wrap' } }}>
+            <TextField label="Nome" value={name} onChange={(_, v) => setName(v || '')} placeholder="Balança da mistura" styles={{ root: { width: 200 } }} />
+            <Dropdown label="Conexão" options={transportOptions} selectedKey={kind} onChange={(_, o) => setKind(o?.key as 'serial' | 'tcp')} styles={{ root: { width: 140 } }} />
+            {kind === 'serial' ? (
+              <>
+                <TextField label="Porta" value={serialPort} onChange={(_, v) => setSerialPort(v || '')} placeholder="COM3 ou /dev/ttyUSB0" styles={{ root: { width: 180 } }} />
+                <Dropdown label="Velocidade" options={baudOptions} selectedKey={baudRate} onChange={(_, o) => setBaudRate(o?.key as number)} styles={{ root: { width: 110 } }} />
+              </>
+            ) : (
+              <>
+                <TextField label="Endereço" value={host} onChange={(_, v) => setHost(v || '')} placeholder="192.168.0.50" styles={{ root: { width: 180 } }} />
+                <TextField label="Porta TCP" value={tcpPort} onChange={(_, v) => setTcpPort(v || '')} placeholder="4001" styles={{ root: { width: 110 } }} />
+              </>
+            )}
+            <Dropdown label="Protocolo" options={scaleProtocolOptions} selectedKey={protocol} onChange={(_, o) => setProtocol(o?.key as ScaleProtocol)} styles={{ root: { width: 220 } }} />
+            <TextField label="Leituras p/ estável" value={stableReadings} onChange={(_, v) => setStableReadings(v || '')} styles={{ root: { width: 130 } }} />
+            <TextField label="Variação aceita (kg)" value={stableBand} onChange={(_, v) => setStableBand(v || '')} placeholder="0" styles={{ root: { width: 150 } }} />
+            <PrimaryButton text={id ? 'Salvar' : 'Adicionar'} onClick={handleSave} disabled={saving} />
+            {id && <DefaultButton text="Cancelar" onClick={resetForm} />}
+          </Stack>
+        </Stack>
+      )}
+      <div style={{ marginTop: 16 }}>
+        {scales.length === 0 && <p>Nenhuma balança configurada nesta estação.</p>}
+        {scales.map(s => (
+          <div key={s.config.id} style={{ padding: '8px 0', borderBottom: '1px solid #eee' }}>
+            <Stack horizontal tokens={{ childrenGap: 12 }} verticalAlign="center" styles={{ root: { flexWrap: 'wrap' } }}>
+              <strong>{s.config.name || s.config.id}</strong>
+              <span>{describeTransport(s.config.transport)} · {s.config.protocol}</span>
+              <span style={{ color: healthColor(s.health) }}>{healthLabel(s.health)}</span>
+              {s.health.state === 'online' && s.reading && <span>{kgFromMg(s.reading.weight).toFixed(3)} kg {s.reading.stable ? '(estável)' : ''}</span>}
+              {s.error && <span style={{ color: '#d13438' }}>⚠️ {s.error}</span>}
+              {operate && s.health.state === 'online' && <DefaultButton text="Tara" onClick={() => run('tare_scale', s.config.id)} />}
+              {operate && s.health.state === 'online' && <DefaultButton text="Zero" onClick={() => run('zero_scale', s.config.id)} />}
+              {operate && s.health.state !== 'online' && <DefaultButton text="🔄 Reconectar" onClick={() => run('reconnect_scale', s.config.id)} />}
+              {manage && s.config.id !== SIMULATOR_ID && <DefaultButton text="Editar" onClick={() => edit(s)} />}
+              {manage && s.config.id !== SIMULATOR_ID && <DefaultButton text="Remover" onClick={() => handleDelete(s)} />}
+            </Stack>
+          </div>
+        ))}
+      </div>
+      {manage && (
+        <div style={{ marginTop: 24 }}>
+          <h3>Simulador de treinamento</h3>
+          <Stack horizontal tokens={{ childrenGap: 12 }} verticalAlign="end" styles={{ root: { flexWrap: 'wrap' } }}>
+            <Dropdown label="Protocolo" options={scaleProtocolOptions} selectedKey={simProtocol} onChange={(_, o) => setSimProtocol(o?.key as ScaleProtocol)} disabled={simulatorOn} styles={{ root: { width: 220 } }} />
+            <PrimaryButton text={simulatorOn ? 'Desligar simulador' : 'Ligar simulador'} onClick={toggleSimulator} />
+            {simulatorOn && (
+              <>
+                <TextField label="Peso no prato (kg)" value={simLoad} onChange={(_, v) => setSimLoad(v || '')} styles={{ root: { width: 150 } }} />
+                <Checkbox label="Em movimento" checked={simMoving} onChange={(_, c) => setSimMoving(!!c)} />
+                <DefaultButton text="Aplicar" onClick={applySimLoad} />
+              </>
+            )}
+          </Stack>
+        </div>
+      )}
+    </div>
+  );
+}
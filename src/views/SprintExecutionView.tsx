@@ -1,6 +1,8 @@
 import { useState, useEffect, useRef } from 'react';
 import { ProgressIndicator, TextField, PrimaryButton, DefaultButton } from '@fluentui/react';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeAuth, kgFromMg, Unit, unitLabels } from '../api';
+import { useSession } from '../SessionContext';
+import ScalePanel from '../components/ScalePanel';
 import './SprintExecutionView.css';
 
 interface SprintItem {
@@ -10,6 +12,20 @@ interface SprintItem {
   };
   target: number;
   actual: number | null;
+  additions?: Addition[];
+  skipped?: boolean;
+  tolerance_status?: 'Pending' | 'InTolerance' | 'OutOfTolerance' | 'Skipped';
+  // target e actual vêm em mg; a unidade da linha é só para exibição
+  unit?: Unit;
+  factor?: number;
+}
+
+interface Addition {
+  id: string;
+  quantity: number;
+  lot: string | null;
+  scale_id: string | null;
+  created_at: string;
 }
 
 interface Sprint {
@@ -19,8 +35,19 @@ interface Sprint {
   itens: SprintItem[];
   operador_id: any;
   comentario: string | null;
+  ratio_adjustment?: RatioAdjustment | null;
 }
 
+interface RatioAdjustment {
+  factor: number;
+  total_mass: number;
+  extra_mass: number;
+  capped: boolean;
+}
+
+// Nem pesado nem marcado como não dosado
+const isPending = (item: SprintItem) => item.actual === null && !item.skipped;
+
 interface Props {
   processoId: string;
   processoNome: string;
@@ -32,10 +59,16 @@ interface Props {
 
 export default function SprintExecutionView({ processoId, processoNome, sprintItems, sprint, onComplete, onCancel }: Props) {
   const [items, setItems] = useState<SprintItem[]>(sprintItems);
-  const [currentIndex, setCurrentIndex] = useState(0);
+  // Ao retomar um rascunho, continua no primeiro item ainda sem peso
+  const [currentIndex, setCurrentIndex] = useState(() => {
+    const pending = sprintItems.findIndex(isPending);
+    return pending === -1 ? Math.max(sprintItems.length - 1, 0) : pending;
+  });
   const [currentWeight, setCurrentWeight] = useState('');
+  const [currentLot, setCurrentLot] = useState('');
   const [updatedSprint, setUpdatedSprint] = useState<Sprint>(sprint);
   const [isSaving, setIsSaving] = useState(false);
+  const { can } = useSession();
   const inputRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
@@ -47,39 +80,116 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
 
   const currentItem = items[currentIndex];
   const totalItems = items.length;
-  const completedItems = items.filter(item => item.actual !== null).length;
+  const completedItems = items.filter(item => !isPending(item)).length;
   const progress = totalItems > 0 ? completedItems / totalItems : 0;
 
+  const handleAbort = async () => {
+    const reason = prompt(`Abortar o sprint #${sprint.numero}? Informe o motivo (opcional):`);
+    if (reason === null) return;
+    try {
+      setIsSaving(true);
+      await invokeAuth('abort_sprint', { sprintId: sprint.id, reason: reason || null });
+      onCancel();
+    } catch (error) {
+      console.error('Erro ao abortar sprint:', error);
+      alert('Erro ao abortar sprint: ' + error);
+      setIsSaving(false);
+    }
+  };
+
   const handleKeyPress = (e: React.KeyboardEvent<HTMLInputElement>) => {
     if (e.key === 'Enter') {
       handleNext();
     }
   };
 
-  const handleNext = () => {
+  // Atualiza itens com o retorno do backend, que pode ter reajustado os
+  // alvos para manter a proporção da fórmula
+  const applySaved = (saved: Sprint): Sprint => {
+    setItems(saved.itens);
+    const newSprint = { ...updatedSprint, itens: saved.itens, ratio_adjustment: saved.ratio_adjustment };
+    setUpdatedSprint(newSprint);
+    return newSprint;
+  };
+
+  // Grava a porção pesada no rascunho, para não perdê-la numa queda
+  const addPortion = async (): Promise<Sprint | null> => {
     const weight = parseFloat(currentWeight);
     if (isNaN(weight)) {
       alert('Peso inválido. Digite um número válido.');
-      return;
+      return null;
+    }
+    try {
+      const saved = await invokeAuth<Sprint>('add_sprint_addition', {
+        sprintId: updatedSprint.id,
+        itemId: currentItem.item.id,
+        quantity: weight,
+        lot: currentLot || null,
+      });
+      setCurrentWeight('');
+      setCurrentLot('');
+      return applySaved(saved);
+    } catch (error) {
+      alert('❌ Erro ao gravar peso: ' + error);
+      return null;
     }
+  };
 
-    // ⚠️ BLOQUEIO: Correção manual de pesos é PROIBIDA
-    // Operador não pode editar valores já registrados
-    
-    // Atualiza item atual com peso
-    const updatedItems = [...items];
-    updatedItems[currentIndex] = {
-      ...currentItem,
-      actual: weight
-    };
-    setItems(updatedItems);
-    
-    // Atualiza sprint object
-    const newSprint = { ...updatedSprint };
-    newSprint.itens = updatedItems;
-    setUpdatedSprint(newSprint);
-    
+  // Lança como porção o peso estável da balança
+  const handleCapture = async (scaleId: string) => {
+    try {
+      applySaved(await invokeAuth<Sprint>('add_sprint_addition_from_scale', {
+        sprintId: updatedSprint.id,
+        itemId: currentItem.item.id,
+        scaleId,
+        lot: currentLot || null,
+      }));
+      setCurrentLot('');
+    } catch (error) {
+      alert('❌ Erro ao capturar peso: ' + error);
+    }
+    inputRef.current?.focus();
+  };
+
+  const handleRemovePortion = async (addition: Addition) => {
+    if (!confirm(`Remover a porção de ${qty(currentItem, addition.quantity)}?`)) return;
+    try {
+      applySaved(await invokeAuth<Sprint>('remove_sprint_addition', {
+        sprintId: updatedSprint.id,
+        itemId: currentItem.item.id,
+        additionId: addition.id,
+      }));
+    } catch (error) {
+      alert('❌ Erro ao remover porção: ' + error);
+    }
+    inputRef.current?.focus();
+  };
+
+  const handleSkip = async () => {
+    if (!confirm(`Registrar "${currentItem.item.nome}" como não dosado? A falta será compensada no próximo sprint.`)) return;
+    let saved: Sprint;
+    try {
+      saved = await invokeAuth<Sprint>('skip_sprint_item', { sprintId: updatedSprint.id, itemId: currentItem.item.id });
+    } catch (error) {
+      alert('❌ Erro ao registrar item: ' + error);
+      return;
+    }
+    const newSprint = applySaved(saved);
     setCurrentWeight('');
+    if (currentIndex < totalItems - 1) {
+      setCurrentIndex(currentIndex + 1);
+    } else {
+      finalizeSprint(newSprint);
+    }
+  };
+
+  const handleNext = async () => {
+    // Com o campo vazio, avança se o item já tem porções lançadas
+    let newSprint: Sprint | null = updatedSprint;
+    if (currentWeight.trim() || !currentItem.additions?.length) {
+      newSprint = await addPortion();
+      if (!newSprint) return;
+    }
 
     // Avança para próximo ou finaliza
     if (currentIndex < totalItems - 1) {
@@ -90,39 +200,76 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
   };
 
   const finalizeSprint = async (finalSprint: Sprint) => {
+    // Itens sem peso só seguem confirmando que não foram dosados
+    const semPeso = finalSprint.itens.filter(isPending);
+    if (semPeso.length > 0) {
+      const nomes = semPeso.map(it => it.item.nome).join(', ');
+      if (!confirm(`⚠️ Itens sem peso: ${nomes}.\nConcluir registrando-os como não dosados? A falta será compensada no próximo sprint.`)) {
+        return;
+      }
+    }
+
+    // Itens fora da tolerância só seguem com a liberação de um supervisor
+    const fora = finalSprint.itens.filter(it => it.tolerance_status === 'OutOfTolerance');
+    if (fora.length > 0) {
+      const nomes = fora.map(it => it.item.nome).join(', ');
+      if (!can('OverrideTolerances')) {
+        alert(`⚠️ Itens fora da tolerância: ${nomes}.\nChame um supervisor para liberar o sprint.`);
+        onCancel();
+        return;
+      }
+      const justification = prompt(`Itens fora da tolerância: ${nomes}.\nJustificativa para liberar o sprint:`);
+      if (!justification || !justification.trim()) {
+        onCancel();
+        return;
+      }
+      try {
+        await invokeAuth('override_sprint_tolerance', { sprintId: finalSprint.id, justification });
+      } catch (error) {
+        alert('❌ Erro ao liberar sprint: ' + error);
+        onCancel();
+        return;
+      }
+    }
+
+    let progress: { complete: boolean };
     try {
       setIsSaving(true);
-      console.log('Salvando sprint com itens:', finalSprint);
-      
       // Salvar sprint no backend
-      await invoke('save_sprint_to_processo', {
-        processoId: processoId,
-        sprint: finalSprint
-      });
-      
-      console.log('Sprint salvo com sucesso! Criando próximo sprint...');
-      
-      // Criar automaticamente o próximo sprint
-      const nextSprint = await invoke<any>('create_sprint_for_processo', {
+      progress = await invokeAuth<{ complete: boolean }>('save_sprint_to_processo', {
         processoId: processoId,
-        remainingSprints: 1,
-        operadorUsername: 'admin'
+        sprint: finalSprint,
+        skipMissing: semPeso.length > 0
       });
-      
-      console.log('Próximo sprint criado:', nextSprint);
-      
-      // Sprint salvo com sucesso - voltar para dashboard do processo
-      setIsSaving(false);
-      onComplete();
-      
     } catch (error) {
-      console.error('Erro ao salvar/criar sprint:', error);
+      console.error('Erro ao salvar sprint:', error);
       alert('❌ Erro ao salvar sprint: ' + error);
       setIsSaving(false);
       onComplete(); // Em caso de erro, volta ao dashboard
+      return;
+    }
+
+    if (progress.complete) {
+      // último sprint planejado: não há próximo a criar
+      alert('✅ Sprint salvo. Todos os sprints planejados foram concluídos; finalize o processo.');
+    } else {
+      try {
+        // Criar automaticamente o próximo sprint
+        await invokeAuth('create_sprint_for_processo', { processoId: processoId });
+      } catch (error) {
+        console.error('Erro ao criar próximo sprint:', error);
+        alert('Sprint salvo, mas o próximo não pôde ser criado: ' + error);
+      }
     }
+
+    // Sprint salvo com sucesso - voltar para dashboard do processo
+    setIsSaving(false);
+    onComplete();
   };
 
+  // Quantidade em mg exibida na unidade da linha
+  const qty = (item: SprintItem, mg: number) => `${(kgFromMg(mg) / (item.factor ?? 1)).toFixed(2)} ${unitLabels[item.unit ?? 'kg']}`;
+
   const getDivergence = (item: SprintItem): number | null => {
     if (item.actual === null) return null;
     return item.actual - item.target;
@@ -135,6 +282,13 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
           <h1>Sprint #{updatedSprint.numero}</h1>
           <h2>{processoNome}</h2>
           {isSaving && <div style={{ color: '#0078d4', fontSize: '16px', marginTop: '8px' }}>💾 Salvando e preparando próximo sprint...</div>}
+          {updatedSprint.ratio_adjustment && (
+            <div style={{ color: updatedSprint.ratio_adjustment.capped ? '#d13438' : '#8a6d00', fontSize: '16px', marginTop: '8px' }}>
+              ⚖️ Alvos reajustados para manter a proporção: +{updatedSprint.ratio_adjustment.extra_mass.toFixed(2)} kg
+              (total {updatedSprint.ratio_adjustment.total_mass.toFixed(2)} kg)
+              {updatedSprint.ratio_adjustment.capped && ' — limitado pela capacidade do misturador'}
+            </div>
+          )}
         </div>
         <div className="sprint-progress-section">
           <div className="progress-label">Item {completedItems + 1} de {totalItems}</div>
@@ -151,15 +305,27 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
           <div className="item-name">🎯 {currentItem.item.nome}</div>
           <div className="target-weight-card">
             <div className="target-label">Peso Sugerido</div>
-            <div className="target-value">{currentItem.target.toFixed(2)} kg</div>
+            <div className="target-value">{qty(currentItem, currentItem.target)}</div>
             <div className="target-hint">
-              {currentItem.target !== 0 && Math.abs(currentItem.target) > 0.1 && (
+              {currentItem.target !== 0 && Math.abs(kgFromMg(currentItem.target)) > 0.1 && (
                 <span>Correção aplicada baseada em sprints anteriores</span>
               )}
             </div>
           </div>
+          <ScalePanel onCapture={handleCapture} disabled={isSaving} />
           <div className="weight-input-area">
-            <label className="input-label">Digite o peso pesado:</label>
+            {(currentItem.additions?.length ?? 0) > 0 && (
+              <div className="input-hint">
+                {currentItem.additions!.map((a, idx) => (
+                  <div key={a.id}>
+                    Porção {idx + 1}: {qty(currentItem, a.quantity)}{a.lot && ` (lote ${a.lot})`}
+                    <button onClick={() => handleRemovePortion(a)} disabled={isSaving} style={{ marginLeft: 8 }}>✖</button>
+                  </div>
+                ))}
+                <strong>Total: {qty(currentItem, currentItem.actual ?? 0)}</strong>
+              </div>
+            )}
+            <label className="input-label">Digite o peso pesado ({unitLabels[currentItem.unit ?? 'kg']}):</label>
             <TextField
               componentRef={inputRef as any}
               value={currentWeight}
@@ -178,7 +344,13 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
                 }
               }}
             />
-            <div className="input-hint">Pressione Enter ou clique em Próximo</div>
+            <TextField
+              label="Lote (opcional)"
+              value={currentLot}
+              onChange={(_, v) => setCurrentLot(v || '')}
+              styles={{ root: { width: '100%', maxWidth: '500px' } }}
+            />
+            <div className="input-hint">Pressione Enter ou clique em Próximo; use “Adicionar porção” para pesar o item em várias vezes</div>
           </div>
           <div className="action-buttons">
             <PrimaryButton
@@ -195,6 +367,32 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
                 } 
               }}
             />
+            <DefaultButton
+              text="➕ Adicionar porção"
+              onClick={() => { addPortion().then(() => inputRef.current?.focus()); }}
+              disabled={isSaving}
+              styles={{ 
+                root: { 
+                  fontSize: '18px', 
+                  padding: '24px 32px',
+                  height: '72px',
+                  minWidth: '180px'
+                } 
+              }}
+            />
+            <DefaultButton
+              text="⏭️ Não dosar"
+              onClick={handleSkip}
+              disabled={isSaving}
+              styles={{ 
+                root: { 
+                  fontSize: '18px', 
+                  padding: '24px 32px',
+                  height: '72px',
+                  minWidth: '180px'
+                } 
+              }}
+            />
             <DefaultButton
               text="❌ Cancelar"
               onClick={onCancel}
@@ -208,6 +406,19 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
                 } 
               }}
             />
+            <DefaultButton
+              text="⛔ Abortar Sprint"
+              onClick={handleAbort}
+              disabled={isSaving}
+              styles={{ 
+                root: { 
+                  fontSize: '18px', 
+                  padding: '24px 32px',
+                  height: '72px',
+                  minWidth: '180px'
+                } 
+              }}
+            />
           </div>
         </div>
 
@@ -218,7 +429,7 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
             {items.map((item, idx) => {
               const divergence = getDivergence(item);
               const isCurrent = idx === currentIndex;
-              const isCompleted = item.actual !== null;
+              const isCompleted = !isPending(item);
 
               return (
                 <div
@@ -230,13 +441,22 @@ export default function SprintExecutionView({ processoId, processoNome, sprintIt
                     <span className="item-name-small">{item.item.nome}</span>
                   </div>
                   <div className="item-weights">
-                    <span className="target">Alvo: {item.target.toFixed(2)} kg</span>
+                    <span className="target">Alvo: {qty(item, item.target)}</span>
+                    {item.skipped && (
+                      <span className="divergence negative">⏭️ não dosado</span>
+                    )}
                     {item.actual !== null && (
                       <>
-                        <span className="actual">Real: {item.actual.toFixed(2)} kg</span>
+                        <span className="actual">Real: {qty(item, item.actual)}</span>
                         <span className={`divergence ${divergence! >= 0 ? 'positive' : 'negative'}`}>
-                          {divergence! >= 0 ? '+' : ''}{divergence!.toFixed(2)} kg
+                          {divergence! >= 0 ? '+' : ''}{qty(item, divergence!)}
                         </span>
+                        {updatedSprint.ratio_adjustment && item.actual < item.target && (
+                          <span className="divergence negative">completar +{qty(item, item.target - item.actual)}</span>
+                        )}
+                        {item.tolerance_status === 'OutOfTolerance' && (
+                          <span className="divergence positive">⚠️ fora da tolerância</span>
+                        )}
                       </>
                     )}
                   </div>
@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
-import { Dropdown, IDropdownOption } from '@fluentui/react';
+import { invokeAuth, kgFromMg, Page } from '../api';
+import { Dropdown, IDropdownOption, DefaultButton } from '@fluentui/react';
+import { useSession } from '../SessionContext';
 
 interface Processo {
   id: string;
@@ -16,9 +17,22 @@ interface Sprint {
   processo_id: string;
   numero: number;
   itens: SprintItem[];
+  status: SprintStatus;
+  status_reason: string | null;
+  status_changed_by: { username: string } | null;
   created_at: string;
 }
 
+type SprintStatus = 'Draft' | 'InProgress' | 'Completed' | 'Aborted' | 'Voided';
+
+const statusLabels: Record<SprintStatus, string> = {
+  Draft: 'Rascunho',
+  InProgress: 'Em pesagem',
+  Completed: 'Concluído',
+  Aborted: 'Abortado',
+  Voided: 'Anulado',
+};
+
 interface SprintItem {
   item: { id: string; nome: string };
   target: number;
@@ -46,6 +60,7 @@ export default function SprintsView() {
   const [selectedProcesso, setSelectedProcesso] = useState<string>('todos');
   const [loading, setLoading] = useState(true);
   const [expandedRows, setExpandedRows] = useState<Set<string>>(new Set());
+  const { can } = useSession();
 
   useEffect(() => {
     loadData();
@@ -58,7 +73,7 @@ export default function SprintsView() {
   const loadData = async () => {
     try {
       setLoading(true);
-      const data = await invoke<Processo[]>('list_processos', { page: 0, pageSize: 1000 });
+      const data = (await invokeAuth<Page<Processo>>('list_processos', { page: 0, pageSize: 1000, sortBy: 'created_at', direction: 'desc' })).items;
       setProcessos(data);
     } catch (error) {
       console.error('Erro ao carregar processos:', error);
@@ -67,6 +82,22 @@ export default function SprintsView() {
     }
   };
 
+  const handleVoid = async (row: SprintRow) => {
+    const reason = prompt(`Motivo da anulação do sprint #${row.sprintNumero}:`);
+    if (reason === null) return;
+    if (!reason.trim()) {
+      alert('Informe o motivo da anulação');
+      return;
+    }
+    try {
+      await invokeAuth('void_sprint', { processoId: row.processoId, sprintId: row.sprintId, reason });
+      await loadData();
+    } catch (error) {
+      console.error('Erro ao anular sprint:', error);
+      alert('Erro ao anular sprint: ' + error);
+    }
+  };
+
   const toggleExpand = (sprintId: string) => {
     const newExpanded = new Set(expandedRows);
     if (newExpanded.has(sprintId)) {
@@ -86,8 +117,8 @@ export default function SprintsView() {
       }
 
       for (const sprint of processo.sprints) {
-        const pesoAlvo = sprint.itens.reduce((sum, item) => sum + item.target, 0);
-        const pesoReal = sprint.itens.reduce((sum, item) => sum + (item.actual || 0), 0);
+        const pesoAlvo = sprint.itens.reduce((sum, item) => sum + kgFromMg(item.target), 0);
+        const pesoReal = sprint.itens.reduce((sum, item) => sum + kgFromMg(item.actual || 0), 0);
         const divergencia = pesoReal - pesoAlvo;
 
         rows.push({
@@ -165,7 +196,9 @@ export default function SprintsView() {
                     {expandedRows.has(row.sprintId) ? '▼' : '▶'}
                   </span>
                   <span>{row.processoNome}</span>
-                  <span>{row.sprintNumero}</span>
+                  <span style={{ textDecoration: row.sprint?.status === 'Voided' ? 'line-through' : 'none' }}>
+                    {row.sprintNumero}
+                  </span>
                   <span>{row.totalItens}</span>
                   <span>{row.pesoAlvo.toFixed(2)}</span>
                   <span>{row.pesoReal.toFixed(2)}</span>
@@ -184,6 +217,20 @@ export default function SprintsView() {
                     backgroundColor: '#faf9f8',
                     borderBottom: '2px solid #edebe9'
                   }}>
+                    <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center', marginBottom: 16 }}>
+                      <div>
+                        <strong>Situação:</strong> {statusLabels[row.sprint.status] ?? row.sprint.status}
+                        {row.sprint.status_reason && (
+                          <span style={{ color: '#605e5c' }}>
+                            {' '}• {row.sprint.status_reason}
+                            {row.sprint.status_changed_by && ` (${row.sprint.status_changed_by.username})`}
+                          </span>
+                        )}
+                      </div>
+                      {row.sprint.status === 'Completed' && can('VoidSprints') && (
+                        <DefaultButton text="🚫 Anular Sprint" onClick={() => handleVoid(row)} />
+                      )}
+                    </div>
                     <h4 style={{ marginTop: 0, marginBottom: 16 }}>📋 Detalhes por Item</h4>
                     <table style={{ width: '100%', borderCollapse: 'collapse' }}>
                       <thead>
@@ -199,7 +246,7 @@ export default function SprintsView() {
                         {row.sprint.itens.map((sprintItem, idx) => {
                           const formulaItem = row.processo?.formula?.itens?.find(fi => fi.item.id === sprintItem.item.id);
                           const baseWeight = formulaItem?.peso || 0;
-                          const actual = sprintItem.actual || 0;
+                          const actual = kgFromMg(sprintItem.actual || 0);
                           const errorVsBase = actual - baseWeight;
                           
                           return (
@@ -209,7 +256,7 @@ export default function SprintsView() {
                                 {baseWeight.toFixed(2)}
                               </td>
                               <td style={{ padding: '8px', textAlign: 'right', border: '1px solid #edebe9' }}>
-                                {sprintItem.target.toFixed(2)}
+                                {kgFromMg(sprintItem.target).toFixed(2)}
                               </td>
                               <td style={{ padding: '8px', textAlign: 'right', border: '1px solid #edebe9' }}>
                                 {actual.toFixed(2)}